@@ -0,0 +1,49 @@
+use criterion::{BatchSize, Criterion, black_box, criterion_group, criterion_main};
+use sudoku_solver::solvers::{
+    backtracking::BacktrackingSolver, heuristic::Heuristic, solver::Solver,
+};
+use sudoku_solver::sudoku::Sudoku;
+
+const PUZZLES: [(&str, &str); 3] = [
+    (
+        "easy",
+        "050000024904005000876240000010002080300000750409017200000900000247000000000600032",
+    ),
+    (
+        "medium",
+        "000000000603140500902500807520090614300000000001005209730800000009000006060010070",
+    ),
+    (
+        "fiendish",
+        "000100597650009310000000004001003700060407000005800900030028000006000003070030001",
+    ),
+];
+
+fn bench_solver<S: Solver>(c: &mut Criterion, name: &str) {
+    let mut group = c.benchmark_group(name);
+    for (difficulty, input) in PUZZLES {
+        group.bench_function(difficulty, |b| {
+            b.iter_batched(
+                || Sudoku::from_string(input).unwrap(),
+                |mut sudoku| S::solve(&mut sudoku).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+// Boards are copied on backtracking branches and used as batch keys.
+fn clone(c: &mut Criterion) {
+    let sudoku = Sudoku::from_string(PUZZLES[0].1).unwrap();
+    c.bench_function("clone", |b| b.iter(|| black_box(&sudoku).clone()));
+}
+
+fn solvers(c: &mut Criterion) {
+    bench_solver::<BacktrackingSolver>(c, "backtracking");
+    bench_solver::<Heuristic>(c, "heuristic");
+    clone(c);
+}
+
+criterion_group!(benches, solvers);
+criterion_main!(benches);
@@ -0,0 +1,162 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
+
+use anyhow::{Result, anyhow};
+
+use crate::{
+    sudoku::Sudoku,
+    symmetry::{Transform, canonical_form, canonical_form_before},
+};
+
+// How repeated boards in a batch are recognised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dedup {
+    // Identical boards only.
+    Exact,
+    // Boards that a symmetry turns into one another. Boards without a
+    // canonical form, such as variants, are matched exactly.
+    Symmetry,
+}
+
+// Keeps the first of each set of repeated boards, in input order.
+pub fn dedup(boards: Vec<Sudoku>, mode: Dedup) -> Vec<Sudoku> {
+    let mut seen = HashSet::new();
+    boards
+        .into_iter()
+        .filter(|board| seen.insert(key(board, mode, None).0))
+        .collect()
+}
+
+// Solves every board but runs `solve` once per distinct board. A repeat gets
+// the earlier solution, carried over by the symmetry relating the two.
+pub fn solve_all<F>(boards: &[Sudoku], mode: Dedup, solve: F) -> Vec<Result<Sudoku>>
+where
+    F: Fn(&mut Sudoku) -> Result<()>,
+{
+    solve_all_until(boards, mode, None, solve)
+}
+
+// Like `solve_all`, but stops looking for symmetries at `deadline` and
+// matches the remaining boards exactly. `solve` should stop there too.
+pub fn solve_all_before<F>(
+    boards: &[Sudoku],
+    mode: Dedup,
+    deadline: Instant,
+    solve: F,
+) -> Vec<Result<Sudoku>>
+where
+    F: Fn(&mut Sudoku) -> Result<()>,
+{
+    solve_all_until(boards, mode, Some(deadline), solve)
+}
+
+fn solve_all_until<F>(
+    boards: &[Sudoku],
+    mode: Dedup,
+    deadline: Option<Instant>,
+    solve: F,
+) -> Vec<Result<Sudoku>>
+where
+    F: Fn(&mut Sudoku) -> Result<()>,
+{
+    // Solutions in the frame of their key, or why solving failed.
+    let mut solved: HashMap<Sudoku, Result<Sudoku, String>> = HashMap::new();
+
+    boards
+        .iter()
+        .map(|board| {
+            let (key, transform) = key(board, mode, deadline);
+            let solution = solved.entry(key).or_insert_with(|| {
+                let mut solution = board.clone();
+                solve(&mut solution).map_err(|e| e.to_string())?;
+                match &transform {
+                    Some(transform) => transform.apply(&solution).map_err(|e| e.to_string()),
+                    None => Ok(solution),
+                }
+            });
+
+            let solution = solution.as_ref().map_err(|e| anyhow!("{}", e))?;
+            match &transform {
+                Some(transform) => transform.inverse().apply(solution),
+                None => Ok(solution.clone()),
+            }
+        })
+        .collect()
+}
+
+// The board repeats are recognised by, and the transform that produced it
+// from `board` when matching up to symmetry.
+fn key(board: &Sudoku, mode: Dedup, deadline: Option<Instant>) -> (Sudoku, Option<Transform>) {
+    if mode == Dedup::Exact {
+        return (board.clone(), None);
+    }
+    let canonical = match deadline {
+        Some(deadline) => canonical_form_before(board, deadline),
+        None => canonical_form(board),
+    };
+    match canonical {
+        Ok((canonical, transform)) => (canonical, Some(transform)),
+        Err(_) => (board.clone(), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cell::Cell,
+        solvers::{backtracking::BacktrackingSolver, solver::Solver},
+    };
+
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    fn boards() -> Vec<Sudoku> {
+        let sudoku = Sudoku::from_string(PUZZLE).unwrap();
+        let turned = Transform::identity(sudoku.shape())
+            .rotate()
+            .apply(&sudoku)
+            .unwrap();
+        vec![sudoku.clone(), turned, sudoku]
+    }
+
+    #[test]
+    fn test_dedup() {
+        assert_eq!(dedup(boards(), Dedup::Exact).len(), 2);
+        assert_eq!(
+            dedup(boards(), Dedup::Symmetry).len(),
+            1,
+            "The rotated board is a repeat"
+        );
+    }
+
+    #[test]
+    fn test_solve_all_solves_repeats_once() {
+        let calls = std::cell::Cell::new(0);
+        let solutions = solve_all(&boards(), Dedup::Symmetry, |board| {
+            calls.set(calls.get() + 1);
+            BacktrackingSolver::solve(board)
+        });
+
+        assert_eq!(calls.get(), 1);
+        assert_eq!(solutions[0].as_ref().unwrap().to_string(), SOLUTION);
+        assert_eq!(solutions[2].as_ref().unwrap().to_string(), SOLUTION);
+        let turned = solutions[1].as_ref().unwrap();
+        assert_eq!(turned[0][8], 5, "The rotated board gets a rotated solution");
+        assert!(turned.is_given(Cell::new(0, 8).unwrap()));
+    }
+
+    #[test]
+    fn test_solve_all_repeats_failures() {
+        let empty = Sudoku::from_string(&"0".repeat(16)).unwrap();
+        let solutions = solve_all(&[empty.clone(), empty], Dedup::Exact, |_| {
+            Err(anyhow!("Unsolvable board"))
+        });
+
+        assert!(solutions.iter().all(Result::is_err));
+    }
+}
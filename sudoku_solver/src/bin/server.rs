@@ -0,0 +1,17 @@
+use std::env;
+
+use anyhow::Result;
+use sudoku_solver::server::{Limits, serve};
+
+fn main() -> Result<()> {
+    let port = match env::args().nth(1) {
+        Some(port) => port.parse()?,
+        None => 8080,
+    };
+
+    println!(
+        "Serving /solve, /validate and /batch on http://127.0.0.1:{}",
+        port
+    );
+    serve(port, Limits::default())
+}
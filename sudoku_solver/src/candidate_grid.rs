@@ -0,0 +1,508 @@
+use std::{fmt, sync::Arc};
+
+use anyhow::Result;
+
+use crate::{
+    constraint::{self, Constraint},
+    regions::Regions,
+    shape::Shape,
+    sudoku::Sudoku,
+    validator::Validator,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Row(usize),
+    Column(usize),
+    Box(usize),
+    Diagonal(usize),
+    Cage(usize),
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Unit::Row(row) => write!(f, "row {}", row + 1),
+            Unit::Column(col) => write!(f, "column {}", col + 1),
+            Unit::Box(b) => write!(f, "box {}", b + 1),
+            Unit::Diagonal(0) => write!(f, "main diagonal"),
+            Unit::Diagonal(_) => write!(f, "anti-diagonal"),
+            Unit::Cage(cage) => write!(f, "cage {}", cage + 1),
+        }
+    }
+}
+
+pub(crate) fn digits(mask: u32) -> impl Iterator<Item = u8> {
+    (1..=Shape::MAX_SIZE as u8).filter(move |digit| mask & (1 << digit) != 0)
+}
+
+// The units of a board shape and its constraints and, for every cell, the
+// units and peers it has. Shared between clones of a grid.
+#[derive(Debug)]
+struct Layout {
+    shape: Shape,
+    regions: Option<Regions>,
+    constraints: Vec<Constraint>,
+    units: Vec<(Unit, Vec<usize>)>,
+    cages: Vec<Vec<usize>>,
+    units_of: Vec<Vec<usize>>,
+    peers: Vec<Vec<usize>>,
+    non_consecutive: Vec<Vec<usize>>,
+}
+
+impl Layout {
+    fn new(shape: Shape, regions: Option<&Regions>, constraints: &[Constraint]) -> Self {
+        let size = shape.size();
+        let boxes_map = regions
+            .cloned()
+            .unwrap_or_else(|| Regions::from_shape(shape));
+        let boxes = (0..size).map(|b| {
+            let cells = boxes_map
+                .cells(b)
+                .iter()
+                .map(|&(row, col)| row * size + col);
+            (Unit::Box(b), cells.collect())
+        });
+        let rows = (0..size).map(|row| {
+            (
+                Unit::Row(row),
+                (0..size).map(|col| row * size + col).collect(),
+            )
+        });
+        let cols = (0..size).map(|col| {
+            (
+                Unit::Column(col),
+                (0..size).map(|row| row * size + col).collect(),
+            )
+        });
+        let houses = constraints.iter().flat_map(|constraint| {
+            constraint.houses(shape).into_iter().map(|(unit, cells)| {
+                let cells = cells.into_iter().map(|(row, col)| row * size + col);
+                (unit, cells.collect())
+            })
+        });
+        let units: Vec<(Unit, Vec<usize>)> = boxes.chain(rows).chain(cols).chain(houses).collect();
+
+        // Killer cages, numbered in constraint order like `Unit::Cage`. They
+        // are not houses, since a cage need not hold every digit.
+        let cages = constraints
+            .iter()
+            .filter_map(|constraint| match constraint {
+                Constraint::Cage { cells, .. } => {
+                    Some(cells.iter().map(|&(row, col)| row * size + col).collect())
+                }
+                _ => None,
+            })
+            .collect();
+
+        // Row, column and box, in that order, then any constraint units.
+        let units_of: Vec<Vec<usize>> = (0..shape.cell_count())
+            .map(|cell| {
+                let (row, col) = (cell / size, cell % size);
+                let mut units_of = vec![size + row, 2 * size + col, boxes_map.region_of(row, col)];
+                units_of
+                    .extend((3 * size..units.len()).filter(|&unit| units[unit].1.contains(&cell)));
+                units_of
+            })
+            .collect();
+
+        // Cells sharing a unit, or a group such as a killer cage.
+        let groups: Vec<Vec<usize>> = constraints
+            .iter()
+            .flat_map(|constraint| constraint.groups(shape))
+            .map(|cells| {
+                cells
+                    .into_iter()
+                    .map(|(row, col)| row * size + col)
+                    .collect()
+            })
+            .collect();
+        let peers = (0..shape.cell_count())
+            .map(|cell| {
+                let mut peers: Vec<usize> = units_of[cell]
+                    .iter()
+                    .map(|&unit| &units[unit].1)
+                    .chain(groups.iter().filter(|group| group.contains(&cell)))
+                    .flatten()
+                    .copied()
+                    .filter(|&other| other != cell)
+                    .collect();
+                peers.sort_unstable();
+                peers.dedup();
+                peers
+            })
+            .collect();
+
+        // Orthogonal neighbours, when they may not hold consecutive digits.
+        let non_consecutive = (0..shape.cell_count())
+            .map(|cell| {
+                if !constraints.contains(&Constraint::NonConsecutive) {
+                    return Vec::new();
+                }
+                constraint::neighbours(
+                    shape,
+                    cell / size,
+                    cell % size,
+                    &constraint::ORTHOGONAL_MOVES,
+                )
+                .map(|(row, col)| row * size + col)
+                .collect()
+            })
+            .collect();
+
+        Self {
+            shape,
+            regions: regions.cloned(),
+            constraints: constraints.to_vec(),
+            units,
+            cages,
+            units_of,
+            peers,
+            non_consecutive,
+        }
+    }
+
+    // Where `unit` sits in `units`, or `None` for cages and for units the
+    // board does not have, such as a diagonal without the constraint.
+    fn unit_index(&self, unit: Unit) -> Option<usize> {
+        let size = self.shape.size();
+        match unit {
+            Unit::Box(b) => (b < size).then_some(b),
+            Unit::Row(row) => (row < size).then_some(size + row),
+            Unit::Column(col) => (col < size).then_some(2 * size + col),
+            Unit::Diagonal(_) => self.units.iter().position(|&(other, _)| other == unit),
+            Unit::Cage(_) => None,
+        }
+    }
+}
+
+// Digits and pencil marks of a board. Cells are numbered row by row
+// (`row * size + col`) and candidates are bitmasks with bit `1 << digit` set
+// for each possible digit.
+#[derive(Debug, Clone)]
+pub struct CandidateGrid {
+    layout: Arc<Layout>,
+    values: Vec<u8>,
+    candidates: Vec<u32>,
+}
+
+impl CandidateGrid {
+    pub fn from_sudoku(board: &Sudoku) -> Self {
+        let shape = board.shape();
+        let mut grid = Self::empty(shape, board.regions(), board.constraints());
+
+        for cell in 0..shape.cell_count() {
+            let (row, col) = (grid.row_of(cell), grid.col_of(cell));
+            grid.values[cell] = board[row][col];
+            if grid.values[cell] == 0 {
+                for digit in grid.all_digits() {
+                    if Validator::is_valid(board, row, col, digit) {
+                        grid.candidates[cell] |= 1 << digit;
+                    }
+                }
+            }
+        }
+
+        grid
+    }
+
+    // Cells with a single candidate are taken as placed.
+    pub fn from_candidates(shape: Shape, masks: &[u32]) -> Result<Self> {
+        if masks.len() != shape.cell_count() {
+            return Err(anyhow::anyhow!(
+                "Expected candidates for {} cells, found {}",
+                shape.cell_count(),
+                masks.len()
+            ));
+        }
+
+        let mut grid = Self::empty(shape, None, &[]);
+        let all = grid.all_digits().fold(0, |mask, digit| mask | 1 << digit);
+        for (cell, &mask) in masks.iter().enumerate() {
+            let mask = mask & all;
+            match mask.count_ones() {
+                0 => {
+                    return Err(anyhow::anyhow!(
+                        "Cell r{}c{} has no candidates",
+                        grid.row_of(cell) + 1,
+                        grid.col_of(cell) + 1
+                    ));
+                }
+                1 => grid.values[cell] = mask.trailing_zeros() as u8,
+                _ => grid.candidates[cell] = mask,
+            }
+        }
+
+        grid.to_sudoku()?;
+        for cell in 0..shape.cell_count() {
+            if grid.values[cell] != 0 {
+                grid.place(cell, grid.values[cell]);
+            }
+        }
+        Ok(grid)
+    }
+
+    fn empty(shape: Shape, regions: Option<&Regions>, constraints: &[Constraint]) -> Self {
+        Self {
+            layout: Arc::new(Layout::new(shape, regions, constraints)),
+            values: vec![0; shape.cell_count()],
+            candidates: vec![0; shape.cell_count()],
+        }
+    }
+
+    pub fn shape(&self) -> Shape {
+        self.layout.shape
+    }
+
+    pub fn regions(&self) -> Option<&Regions> {
+        self.layout.regions.as_ref()
+    }
+
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.layout.constraints
+    }
+
+    pub fn size(&self) -> usize {
+        self.layout.shape.size()
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn all_digits(&self) -> std::ops::RangeInclusive<u8> {
+        1..=self.size() as u8
+    }
+
+    pub fn cell(&self, row: usize, col: usize) -> usize {
+        row * self.size() + col
+    }
+
+    pub fn row_of(&self, cell: usize) -> usize {
+        cell / self.size()
+    }
+
+    pub fn col_of(&self, cell: usize) -> usize {
+        cell % self.size()
+    }
+
+    pub fn box_of(&self, cell: usize) -> usize {
+        self.layout.units_of[cell][2]
+    }
+
+    // Boxes first, then rows, then columns.
+    pub fn units(&self) -> impl Iterator<Item = Unit> + '_ {
+        self.layout.units.iter().map(|&(unit, _)| unit)
+    }
+
+    // Empty for a unit the board does not have.
+    pub fn unit_cells(&self, unit: Unit) -> &[usize] {
+        if let Unit::Cage(cage) = unit {
+            return self.layout.cages.get(cage).map_or(&[], Vec::as_slice);
+        }
+        self.layout
+            .unit_index(unit)
+            .map_or(&[], |index| &self.layout.units[index].1)
+    }
+
+    pub fn units_of(&self, cell: usize) -> impl Iterator<Item = Unit> + '_ {
+        self.layout.units_of[cell]
+            .iter()
+            .map(|&unit| self.layout.units[unit].0)
+    }
+
+    pub fn peers(&self, cell: usize) -> &[usize] {
+        &self.layout.peers[cell]
+    }
+
+    pub fn sees(&self, a: usize, b: usize) -> bool {
+        self.layout.peers[a].binary_search(&b).is_ok()
+    }
+
+    pub fn value(&self, cell: usize) -> u8 {
+        self.values[cell]
+    }
+
+    pub fn candidates(&self, cell: usize) -> u32 {
+        self.candidates[cell]
+    }
+
+    pub fn candidate_digits(&self, cell: usize) -> impl Iterator<Item = u8> + use<> {
+        digits(self.candidates[cell])
+    }
+
+    pub fn has_candidate(&self, cell: usize, digit: u8) -> bool {
+        self.candidates[cell] & (1 << digit) != 0
+    }
+
+    pub fn place(&mut self, cell: usize, digit: u8) {
+        self.values[cell] = digit;
+        self.candidates[cell] = 0;
+        for &peer in &self.layout.peers[cell] {
+            self.candidates[peer] &= !(1 << digit);
+        }
+        for &neighbour in &self.layout.non_consecutive[cell] {
+            self.candidates[neighbour] &= !(1 << (digit - 1) | 1 << (digit + 1));
+        }
+    }
+
+    pub fn eliminate(&mut self, cell: usize, digit: u8) {
+        self.candidates[cell] &= !(1 << digit);
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.values.iter().all(|&value| value != 0)
+    }
+
+    pub fn has_contradiction(&self) -> bool {
+        (0..self.cell_count()).any(|cell| self.values[cell] == 0 && self.candidates[cell] == 0)
+    }
+
+    pub fn to_sudoku(&self) -> Result<Sudoku> {
+        let board = self
+            .values
+            .chunks(self.size())
+            .map(<[u8]>::to_vec)
+            .collect();
+        let sudoku = match self.regions() {
+            Some(regions) => Sudoku::jigsaw(board, regions.clone())?,
+            None => Sudoku::with_shape(board, self.shape())?,
+        };
+        sudoku.with_constraints(self.constraints().to_vec())
+    }
+}
+
+impl PartialEq for CandidateGrid {
+    fn eq(&self, other: &Self) -> bool {
+        self.shape() == other.shape()
+            && self.regions() == other.regions()
+            && self.constraints() == other.constraints()
+            && self.values == other.values
+            && self.candidates == other.candidates
+    }
+}
+
+impl Eq for CandidateGrid {}
+
+impl From<&Sudoku> for CandidateGrid {
+    fn from(board: &Sudoku) -> Self {
+        Self::from_sudoku(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_cells() {
+        let grid = CandidateGrid::from_sudoku(&Sudoku::from_string(&"0".repeat(81)).unwrap());
+
+        assert_eq!(
+            grid.unit_cells(Unit::Row(1)),
+            [9, 10, 11, 12, 13, 14, 15, 16, 17]
+        );
+        assert_eq!(
+            grid.unit_cells(Unit::Column(2)),
+            [2, 11, 20, 29, 38, 47, 56, 65, 74]
+        );
+        assert_eq!(
+            grid.unit_cells(Unit::Box(4)),
+            [30, 31, 32, 39, 40, 41, 48, 49, 50]
+        );
+    }
+
+    #[test]
+    fn test_unit_cells_with_rectangular_boxes() {
+        let grid = CandidateGrid::from_sudoku(&Sudoku::from_string(&"0".repeat(36)).unwrap());
+
+        assert_eq!(grid.unit_cells(Unit::Box(1)), [3, 4, 5, 9, 10, 11]);
+        assert_eq!(grid.peers(0).len(), 12);
+        assert_eq!(grid.units().count(), 18);
+    }
+
+    #[test]
+    fn test_unit_cells_of_cages_and_missing_units() {
+        let sudoku = Sudoku::from_string(&"0".repeat(81))
+            .unwrap()
+            .with_constraints(vec![Constraint::Cage {
+                sum: 4,
+                cells: vec![(0, 0), (1, 0)],
+            }])
+            .unwrap();
+        let grid = CandidateGrid::from_sudoku(&sudoku);
+
+        assert_eq!(grid.unit_cells(Unit::Cage(0)), [0, 9]);
+        assert!(grid.unit_cells(Unit::Cage(1)).is_empty());
+        assert!(grid.unit_cells(Unit::Diagonal(0)).is_empty());
+        assert!(grid.unit_cells(Unit::Row(9)).is_empty());
+        assert_eq!(grid.units().count(), 27, "Cages are not houses");
+    }
+
+    #[test]
+    fn test_place_updates_peers() {
+        let sudoku = Sudoku::from_string(&"0".repeat(81)).unwrap();
+        let mut grid = CandidateGrid::from_sudoku(&sudoku);
+
+        grid.place(0, 5);
+
+        assert_eq!(grid.value(0), 5);
+        assert!(!grid.has_candidate(8, 5), "Row peer should lose 5");
+        assert!(!grid.has_candidate(72, 5), "Column peer should lose 5");
+        assert!(!grid.has_candidate(20, 5), "Box peer should lose 5");
+        assert!(grid.has_candidate(40, 5), "Unrelated cell should keep 5");
+        assert_eq!(grid.peers(0).len(), 20);
+    }
+
+    #[test]
+    fn test_from_sudoku_uses_validator_rules() {
+        let sudoku = Sudoku::from_string(
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079",
+        )
+        .unwrap();
+        let grid = CandidateGrid::from(&sudoku);
+
+        assert_eq!(grid.value(0), 5);
+        assert_eq!(
+            grid.candidates(0),
+            0,
+            "Filled cell should have no candidates"
+        );
+        assert_eq!(grid.candidate_digits(2).collect::<Vec<_>>(), vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn test_eliminate_and_convert_back() {
+        let sudoku = Sudoku::from_string(&"0".repeat(81)).unwrap();
+        let mut grid = CandidateGrid::from_sudoku(&sudoku);
+
+        grid.eliminate(10, 3);
+        grid.place(0, 7);
+
+        assert!(!grid.has_candidate(10, 3));
+        assert!(grid.has_candidate(10, 4));
+        assert!(!grid.is_solved());
+        let board = grid.to_sudoku().unwrap();
+        assert_eq!(board[0][0], 7);
+        assert_eq!(board.to_string().matches('0').count(), 80);
+    }
+
+    #[test]
+    fn test_diagonal_units() {
+        let sudoku = Sudoku::from_string(&"0".repeat(81))
+            .unwrap()
+            .with_constraints(vec![Constraint::Diagonals])
+            .unwrap();
+        let grid = CandidateGrid::from_sudoku(&sudoku);
+
+        assert_eq!(grid.units().count(), 29);
+        assert_eq!(grid.unit_cells(Unit::Diagonal(1))[..3], [8, 16, 24]);
+        assert_eq!(grid.peers(0).len(), 26);
+        assert_eq!(grid.peers(40).len(), 32, "Centre lies on both diagonals");
+        assert!(grid.sees(0, 80));
+        assert_eq!(
+            grid.to_sudoku().unwrap().constraints(),
+            [Constraint::Diagonals]
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt;
+
+use anyhow::{Result, anyhow};
+
+use crate::shape::Shape;
+
+// A position on the board, zero-based. Whether it lies on a particular board
+// is checked by the board itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Cell {
+    row: u8,
+    col: u8,
+}
+
+impl Cell {
+    pub fn new(row: usize, col: usize) -> Result<Self> {
+        if row >= Shape::MAX_SIZE || col >= Shape::MAX_SIZE {
+            return Err(anyhow!("Cell r{}c{} is off the board", row + 1, col + 1));
+        }
+
+        Ok(Self {
+            row: row as u8,
+            col: col as u8,
+        })
+    }
+
+    pub fn row(self) -> usize {
+        self.row as usize
+    }
+
+    pub fn col(self) -> usize {
+        self.col as usize
+    }
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "r{}c{}", self.row + 1, self.col + 1)
+    }
+}
+
+// A digit from 1 up to the largest supported board size. Empty cells have
+// no digit rather than a zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Digit(u8);
+
+impl Digit {
+    pub fn new(value: u8) -> Result<Self> {
+        if value == 0 || value as usize > Shape::MAX_SIZE {
+            return Err(anyhow!("{} is not a digit", value));
+        }
+        Ok(Self(value))
+    }
+
+    pub fn get(self) -> u8 {
+        self.0
+    }
+}
+
+impl TryFrom<u8> for Digit {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        Self::new(value)
+    }
+}
+
+impl fmt::Display for Digit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", Shape::symbol(self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cell_bounds() {
+        let cell = Cell::new(2, 8).unwrap();
+
+        assert_eq!((cell.row(), cell.col()), (2, 8));
+        assert_eq!(cell.to_string(), "r3c9");
+        assert!(Cell::new(25, 0).is_err(), "No board has 26 rows");
+    }
+
+    #[test]
+    fn test_digit_range() {
+        assert_eq!(Digit::new(7).unwrap().get(), 7);
+        assert_eq!(Digit::new(16).unwrap().to_string(), "G");
+        assert!(Digit::new(0).is_err(), "Zero is an empty cell, not a digit");
+        assert!(Digit::try_from(26).is_err());
+    }
+}
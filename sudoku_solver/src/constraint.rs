@@ -0,0 +1,729 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::{candidate_grid::Unit, shape::Shape, validator::Board};
+
+// Rules a puzzle adds on top of rows, columns and boxes. Any number of them
+// can be combined. In JSON each one is an object tagged by `type`, e.g.
+// `{"type": "diagonals"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Constraint {
+    // X-Sudoku: both main diagonals hold every digit once.
+    Diagonals,
+    // Killer Sudoku: distinct digits adding up to `sum`.
+    Cage {
+        sum: u32,
+        cells: Vec<(usize, usize)>,
+    },
+    // Cells a chess knight's move apart hold different digits.
+    AntiKnight,
+    // Cells that touch, even diagonally, hold different digits.
+    AntiKing,
+    // Orthogonally adjacent cells never hold consecutive digits.
+    NonConsecutive,
+    // Shaded cells that hold even digits.
+    Even {
+        cells: Vec<(usize, usize)>,
+    },
+    // Shaded cells that hold odd digits.
+    Odd {
+        cells: Vec<(usize, usize)>,
+    },
+    // Digits rise strictly from the bulb, the first cell, to the tip.
+    Thermometer {
+        cells: Vec<(usize, usize)>,
+    },
+    // The digits along the arrow add up to the digit in its circle. They
+    // may repeat unless a row, column or box says otherwise.
+    Arrow {
+        circle: (usize, usize),
+        cells: Vec<(usize, usize)>,
+    },
+    // Kropki white dot between neighbours holding consecutive digits.
+    WhiteDot {
+        cells: [(usize, usize); 2],
+    },
+    // Kropki black dot between neighbours where one digit doubles the other.
+    BlackDot {
+        cells: [(usize, usize); 2],
+    },
+}
+
+const KNIGHT_MOVES: [(isize, isize); 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+
+const KING_MOVES: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+pub(crate) const ORTHOGONAL_MOVES: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+impl Constraint {
+    // Rejects constraints that cannot apply to a board of this shape, cages
+    // that overlap and cells shaded both even and odd.
+    pub fn check_all(shape: Shape, constraints: &[Constraint]) -> Result<()> {
+        let mut caged = vec![false; shape.cell_count()];
+        let mut parity = vec![None; shape.cell_count()];
+        for constraint in constraints {
+            let cells = constraint.cells();
+            for &(row, col) in &cells {
+                if row >= shape.size() || col >= shape.size() {
+                    return Err(anyhow!("Cell r{}c{} is off the board", row + 1, col + 1));
+                }
+            }
+
+            match constraint {
+                Constraint::Cage { sum, cells } => {
+                    if cells.is_empty() || cells.len() > shape.size() {
+                        return Err(anyhow!("Cage must have 1 to {} cells", shape.size()));
+                    }
+                    for &(row, col) in cells {
+                        let cell = row * shape.size() + col;
+                        if caged[cell] {
+                            return Err(anyhow!(
+                                "r{}c{} is in more than one cage",
+                                row + 1,
+                                col + 1
+                            ));
+                        }
+                        caged[cell] = true;
+                    }
+                    if !sum_fits(shape.size(), 0, 0, cells.len(), *sum) {
+                        return Err(anyhow!(
+                            "No {} distinct digits add up to {}",
+                            cells.len(),
+                            sum
+                        ));
+                    }
+                }
+                Constraint::Even { .. } | Constraint::Odd { .. } => {
+                    let even = matches!(constraint, Constraint::Even { .. });
+                    for &(row, col) in &cells {
+                        let cell = row * shape.size() + col;
+                        if *parity[cell].get_or_insert(even) != even {
+                            return Err(anyhow!(
+                                "r{}c{} is shaded both even and odd",
+                                row + 1,
+                                col + 1
+                            ));
+                        }
+                    }
+                }
+                Constraint::Thermometer { cells } => {
+                    if !(2..=shape.size()).contains(&cells.len()) {
+                        return Err(anyhow!("Thermometer must have 2 to {} cells", shape.size()));
+                    }
+                    if (1..cells.len()).any(|i| cells[..i].contains(&cells[i])) {
+                        return Err(anyhow!("Thermometer crosses itself"));
+                    }
+                }
+                Constraint::Arrow { circle, cells }
+                    if cells.is_empty() || cells.contains(circle) =>
+                {
+                    return Err(anyhow!(
+                        "Arrow from r{}c{} needs cells outside its circle",
+                        circle.0 + 1,
+                        circle.1 + 1
+                    ));
+                }
+                Constraint::WhiteDot { cells: [a, b] } | Constraint::BlackDot { cells: [a, b] }
+                    if a.0.abs_diff(b.0) + a.1.abs_diff(b.1) != 1 =>
+                {
+                    return Err(anyhow!(
+                        "Dot between r{}c{} and r{}c{} joins no neighbours",
+                        a.0 + 1,
+                        a.1 + 1,
+                        b.0 + 1,
+                        b.1 + 1
+                    ));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    // Cells named by the constraint; empty for rules covering the board.
+    pub fn cells(&self) -> Vec<(usize, usize)> {
+        match self {
+            Constraint::Cage { cells, .. }
+            | Constraint::Even { cells }
+            | Constraint::Odd { cells }
+            | Constraint::Thermometer { cells } => cells.clone(),
+            Constraint::Arrow { circle, cells } => [*circle].iter().chain(cells).copied().collect(),
+            Constraint::WhiteDot { cells } | Constraint::BlackDot { cells } => cells.to_vec(),
+            _ => Vec::new(),
+        }
+    }
+
+    // Extra units whose cells must all differ, as (row, col) positions.
+    pub fn houses(&self, shape: Shape) -> Vec<(Unit, Vec<(usize, usize)>)> {
+        let size = shape.size();
+        match self {
+            Constraint::Diagonals => vec![
+                (Unit::Diagonal(0), (0..size).map(|i| (i, i)).collect()),
+                (
+                    Unit::Diagonal(1),
+                    (0..size).map(|i| (i, size - 1 - i)).collect(),
+                ),
+            ],
+            _ => Vec::new(),
+        }
+    }
+
+    // Cells that must differ without having to hold every digit.
+    pub fn groups(&self, shape: Shape) -> Vec<Vec<(usize, usize)>> {
+        let pairs = |moves: &[(isize, isize)]| {
+            let cells =
+                (0..shape.size()).flat_map(|row| (0..shape.size()).map(move |col| (row, col)));
+            cells
+                .flat_map(|cell| {
+                    neighbours(shape, cell.0, cell.1, moves)
+                        .filter(move |&other| other > cell)
+                        .map(move |other| vec![cell, other])
+                        .collect::<Vec<_>>()
+                })
+                .collect()
+        };
+        match self {
+            Constraint::Cage { cells, .. } | Constraint::Thermometer { cells } => {
+                vec![cells.clone()]
+            }
+            Constraint::WhiteDot { cells } | Constraint::BlackDot { cells } => vec![cells.to_vec()],
+            Constraint::AntiKnight => pairs(&KNIGHT_MOVES),
+            Constraint::AntiKing => pairs(&KING_MOVES),
+            _ => Vec::new(),
+        }
+    }
+
+    // Whether `value` may go at (row, col) given the digits already placed.
+    pub fn allows<B: Board + ?Sized>(&self, board: &B, row: usize, col: usize, value: u8) -> bool {
+        let Some(shape) = board.shape() else {
+            return false;
+        };
+        let size = shape.size();
+        // Digits are bit positions below, so keep them on the board.
+        if value == 0 || value as usize > size {
+            return false;
+        }
+        let differs = |moves: &[(isize, isize)]| {
+            neighbours(shape, row, col, moves).all(|(r, c)| board.value(r, c) != value)
+        };
+        match self {
+            Constraint::Diagonals => {
+                (row != col || (0..size).all(|i| board.value(i, i) != value))
+                    && (row + col != size - 1
+                        || (0..size).all(|i| board.value(i, size - 1 - i) != value))
+            }
+            Constraint::Cage { sum, cells } => {
+                if !cells.contains(&(row, col)) {
+                    return true;
+                }
+                let (mut used, mut total, mut empty) = (1u32 << value, value as u32, 0);
+                for &other in cells.iter().filter(|&&other| other != (row, col)) {
+                    match board.value(other.0, other.1) {
+                        0 => empty += 1,
+                        digit if digit == value => return false,
+                        digit => {
+                            used |= 1 << digit;
+                            total += digit as u32;
+                        }
+                    }
+                }
+                sum_fits(size, used, total, empty, *sum)
+            }
+            Constraint::AntiKnight => differs(&KNIGHT_MOVES),
+            Constraint::AntiKing => differs(&KING_MOVES),
+            Constraint::NonConsecutive => neighbours(shape, row, col, &ORTHOGONAL_MOVES)
+                .all(|(r, c)| board.value(r, c) == 0 || board.value(r, c).abs_diff(value) != 1),
+            Constraint::Even { cells } => value.is_multiple_of(2) || !cells.contains(&(row, col)),
+            Constraint::Odd { cells } => !value.is_multiple_of(2) || !cells.contains(&(row, col)),
+            Constraint::Thermometer { cells } => {
+                let Some(i) = cells.iter().position(|&cell| cell == (row, col)) else {
+                    return true;
+                };
+                let value = value as usize;
+                value > i
+                    && value + (cells.len() - 1 - i) <= size
+                    && cells.iter().enumerate().all(|(j, &(r, c))| {
+                        let other = board.value(r, c) as usize;
+                        j == i
+                            || other == 0
+                            || (j < i && other + (i - j) <= value)
+                            || (j > i && value + (j - i) <= other)
+                    })
+            }
+            Constraint::Arrow { circle, cells } => {
+                let is_circle = *circle == (row, col);
+                if !is_circle && !cells.contains(&(row, col)) {
+                    return true;
+                }
+                let target = if is_circle {
+                    value as usize
+                } else {
+                    board.value(circle.0, circle.1) as usize
+                };
+                let (mut total, mut empty) = (0, 0);
+                for &cell in cells {
+                    match (cell == (row, col), board.value(cell.0, cell.1)) {
+                        (true, _) => total += value as usize,
+                        (false, 0) => empty += 1,
+                        (false, other) => total += other as usize,
+                    }
+                }
+                match target {
+                    0 => total + empty <= size,
+                    _ => total + empty <= target && total + empty * size >= target,
+                }
+            }
+            Constraint::WhiteDot { cells } | Constraint::BlackDot { cells } => {
+                let other = match cells {
+                    [a, b] if *a == (row, col) => *b,
+                    [a, b] if *b == (row, col) => *a,
+                    _ => return true,
+                };
+                let other = board.value(other.0, other.1);
+                let white = matches!(self, Constraint::WhiteDot { .. });
+                other == 0
+                    || (white && other.abs_diff(value) == 1)
+                    || (!white && (other == 2 * value || value == 2 * other))
+            }
+        }
+    }
+
+    pub fn is_satisfied<B: Board + ?Sized>(&self, board: &B) -> bool {
+        let Some(shape) = board.shape() else {
+            return false;
+        };
+        let size = shape.size();
+        let in_range =
+            (0..size).all(|row| (0..size).all(|col| board.value(row, col) as usize <= size));
+        if !in_range {
+            return false;
+        }
+        let houses = self.houses(shape).into_iter().map(|(_, cells)| cells);
+        let distinct = houses.chain(self.groups(shape)).all(|cells| {
+            let mut seen = 0u32;
+            cells.iter().all(|&(row, col)| {
+                let value = board.value(row, col);
+                let repeated = value != 0 && seen & (1 << value) != 0;
+                seen |= 1 << value;
+                !repeated
+            })
+        });
+        // Shading only restricts digits already placed.
+        let placed = |cells: &[(usize, usize)], even| {
+            cells.iter().all(|&(row, col)| {
+                let value = board.value(row, col);
+                value == 0 || value.is_multiple_of(2) == even
+            })
+        };
+
+        match self {
+            Constraint::Diagonals | Constraint::AntiKnight | Constraint::AntiKing => distinct,
+            Constraint::Cage { sum, cells } => {
+                let values = cells.iter().map(|&(row, col)| board.value(row, col));
+                let used = values.clone().fold(0, |used, value| used | 1 << value) & !1;
+                let total = values.clone().map(u32::from).sum();
+                let empty = values.filter(|&value| value == 0).count();
+                distinct && sum_fits(size, used, total, empty, *sum)
+            }
+            Constraint::NonConsecutive => (0..size).all(|row| {
+                (0..size).all(|col| {
+                    let value = board.value(row, col);
+                    value == 0 || self.allows(board, row, col, value)
+                })
+            }),
+            Constraint::Even { cells } => placed(cells, true),
+            Constraint::Odd { cells } => placed(cells, false),
+            // Each placed digit must fit the others already on the line.
+            Constraint::Thermometer { .. }
+            | Constraint::Arrow { .. }
+            | Constraint::WhiteDot { .. }
+            | Constraint::BlackDot { .. } => {
+                distinct
+                    && self.cells().into_iter().all(|(row, col)| {
+                        let value = board.value(row, col);
+                        value == 0 || self.allows(board, row, col, value)
+                    })
+            }
+        }
+    }
+}
+
+// Cells reached from (row, col) by the given moves that lie on the board.
+pub(crate) fn neighbours(
+    shape: Shape,
+    row: usize,
+    col: usize,
+    moves: &[(isize, isize)],
+) -> impl Iterator<Item = (usize, usize)> + '_ {
+    moves.iter().filter_map(move |&(dr, dc)| {
+        let (r, c) = (row.checked_add_signed(dr)?, col.checked_add_signed(dc)?);
+        (r < shape.size() && c < shape.size()).then_some((r, c))
+    })
+}
+
+// Bounds check: whether `empty` more distinct digits outside `used` could
+// bring `total` up to exactly `sum`. Exact once no cells are empty.
+fn sum_fits(size: usize, used: u32, total: u32, empty: usize, sum: u32) -> bool {
+    if total > sum {
+        return false;
+    }
+    let free: Vec<u32> = (1..=size as u32)
+        .filter(|digit| used & (1 << digit) == 0)
+        .collect();
+    if free.len() < empty {
+        return false;
+    }
+    let low: u32 = free[..empty].iter().sum();
+    let high: u32 = free[free.len() - empty..].iter().sum();
+    (low..=high).contains(&(sum - total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagonal_houses() {
+        let houses = Constraint::Diagonals.houses(Shape::for_size(4).unwrap());
+
+        assert_eq!(houses[0].1, vec![(0, 0), (1, 1), (2, 2), (3, 3)]);
+        assert_eq!(houses[1].1, vec![(0, 3), (1, 2), (2, 1), (3, 0)]);
+    }
+
+    #[test]
+    fn test_diagonals_allow_and_satisfy() {
+        let board = vec![
+            vec![1, 0, 0, 0],
+            vec![0, 0, 0, 0],
+            vec![0, 0, 0, 0],
+            vec![2, 0, 0, 0],
+        ];
+
+        assert!(!Constraint::Diagonals.allows(&board, 2, 2, 1));
+        assert!(Constraint::Diagonals.allows(&board, 2, 2, 3));
+        assert!(
+            !Constraint::Diagonals.allows(&board, 1, 2, 2),
+            "Anti-diagonal"
+        );
+        assert!(
+            Constraint::Diagonals.allows(&board, 1, 0, 1),
+            "Off the diagonals"
+        );
+        assert!(Constraint::Diagonals.is_satisfied(&board));
+
+        let mut repeated = board.clone();
+        repeated[2][1] = 2;
+        assert!(!Constraint::Diagonals.is_satisfied(&repeated));
+    }
+
+    #[test]
+    fn test_json_tag() {
+        let json = serde_json::to_string(&Constraint::Diagonals).unwrap();
+
+        assert_eq!(json, r#"{"type":"diagonals"}"#);
+        assert_eq!(
+            serde_json::from_str::<Constraint>(&json).unwrap(),
+            Constraint::Diagonals
+        );
+    }
+
+    #[test]
+    fn test_cage_allows_and_satisfies() {
+        let cage = Constraint::Cage {
+            sum: 8,
+            cells: vec![(0, 0), (0, 1), (1, 0)],
+        };
+        let mut board = vec![vec![0; 4]; 4];
+        board[0][0] = 1;
+
+        assert!(cage.allows(&board, 0, 1, 3), "1 + 3 leaves 4");
+        assert!(!cage.allows(&board, 0, 1, 2), "1 + 2 leaves 5");
+        assert!(
+            !cage.allows(&board, 0, 1, 1),
+            "Digits in a cage are distinct"
+        );
+        assert!(cage.allows(&board, 3, 3, 4), "Outside the cage");
+
+        board[0][1] = 3;
+        board[1][0] = 1;
+        assert!(!cage.is_satisfied(&board), "1 is repeated");
+        board[1][0] = 2;
+        assert!(!cage.is_satisfied(&board), "1 + 3 + 2 is not 8");
+        board[1][0] = 4;
+        assert!(cage.is_satisfied(&board));
+    }
+
+    #[test]
+    fn test_check_all_rejects_bad_cages() {
+        let shape = Shape::for_size(4).unwrap();
+        let cage = |sum, cells: &[(usize, usize)]| Constraint::Cage {
+            sum,
+            cells: cells.to_vec(),
+        };
+
+        assert!(Constraint::check_all(shape, &[cage(3, &[(0, 0), (0, 1)])]).is_ok());
+        assert!(
+            Constraint::check_all(shape, &[cage(3, &[(0, 0), (0, 1)]), cage(4, &[(0, 1)])])
+                .is_err(),
+            "Cages overlap"
+        );
+        assert!(
+            Constraint::check_all(shape, &[cage(8, &[(0, 0), (0, 1)])]).is_err(),
+            "Two digits up to 4 add up to at most 7"
+        );
+        assert!(Constraint::check_all(shape, &[cage(2, &[(4, 0)])]).is_err());
+    }
+
+    #[test]
+    fn test_cage_json() {
+        let cage: Constraint =
+            serde_json::from_str(r#"{"type":"cage","sum":7,"cells":[[0,0],[0,1]]}"#).unwrap();
+
+        assert_eq!(
+            cage,
+            Constraint::Cage {
+                sum: 7,
+                cells: vec![(0, 0), (0, 1)]
+            }
+        );
+    }
+
+    #[test]
+    fn test_anti_knight_and_anti_king() {
+        let mut board = vec![vec![0; 4]; 4];
+        board[0][0] = 1;
+
+        assert!(!Constraint::AntiKnight.allows(&board, 1, 2, 1));
+        assert!(Constraint::AntiKnight.allows(&board, 1, 1, 1));
+        assert!(!Constraint::AntiKing.allows(&board, 1, 1, 1));
+        assert!(Constraint::AntiKing.allows(&board, 1, 2, 1));
+
+        board[2][1] = 1;
+        assert!(!Constraint::AntiKnight.is_satisfied(&board));
+        assert!(Constraint::AntiKing.is_satisfied(&board));
+        assert_eq!(
+            Constraint::AntiKnight
+                .groups(Shape::for_size(4).unwrap())
+                .len(),
+            24,
+            "Each knight move is counted once"
+        );
+    }
+
+    #[test]
+    fn test_non_consecutive() {
+        let mut board = vec![vec![0; 4]; 4];
+        board[1][1] = 2;
+
+        assert!(!Constraint::NonConsecutive.allows(&board, 0, 1, 3));
+        assert!(!Constraint::NonConsecutive.allows(&board, 1, 2, 1));
+        assert!(
+            Constraint::NonConsecutive.allows(&board, 0, 0, 3),
+            "Diagonal neighbours may be consecutive"
+        );
+        assert!(Constraint::NonConsecutive.is_satisfied(&board));
+
+        board[2][1] = 3;
+        assert!(!Constraint::NonConsecutive.is_satisfied(&board));
+    }
+
+    #[test]
+    fn test_even_and_odd() {
+        let even = Constraint::Even {
+            cells: vec![(0, 0)],
+        };
+        let odd = Constraint::Odd {
+            cells: vec![(0, 1)],
+        };
+        let mut board = vec![vec![0; 4]; 4];
+
+        assert!(even.allows(&board, 0, 0, 2));
+        assert!(!even.allows(&board, 0, 0, 3));
+        assert!(!odd.allows(&board, 0, 1, 4));
+        assert!(odd.allows(&board, 1, 1, 4), "Unshaded cells take any digit");
+
+        board[0][1] = 2;
+        assert!(even.is_satisfied(&board));
+        assert!(!odd.is_satisfied(&board));
+
+        let shape = Shape::for_size(4).unwrap();
+        let clash = Constraint::Odd {
+            cells: vec![(0, 0)],
+        };
+        assert!(Constraint::check_all(shape, &[even.clone(), odd]).is_ok());
+        assert!(
+            Constraint::check_all(shape, &[even, clash]).is_err(),
+            "r1c1 cannot be both even and odd"
+        );
+    }
+
+    #[test]
+    fn test_digits_off_the_board() {
+        let cage = Constraint::Cage {
+            sum: 10,
+            cells: vec![(0, 0), (0, 1)],
+        };
+        let odd = Constraint::Odd {
+            cells: vec![(0, 0)],
+        };
+        let mut board = vec![vec![0; 4]; 4];
+
+        for constraint in [&cage, &odd, &Constraint::AntiKing] {
+            assert!(!constraint.allows(&board, 0, 0, 0));
+            assert!(!constraint.allows(&board, 0, 0, 5));
+            assert!(!constraint.allows(&board, 0, 0, 33));
+        }
+
+        board[0][0] = 33;
+        assert!(!cage.is_satisfied(&board));
+        assert!(!odd.is_satisfied(&board));
+        assert!(!Constraint::AntiKing.is_satisfied(&board));
+    }
+
+    #[test]
+    fn test_variant_json() {
+        let constraints = vec![
+            Constraint::AntiKnight,
+            Constraint::AntiKing,
+            Constraint::NonConsecutive,
+            Constraint::Even {
+                cells: vec![(0, 0)],
+            },
+        ];
+        let json = serde_json::to_string(&constraints).unwrap();
+
+        assert_eq!(
+            json,
+            r#"[{"type":"anti_knight"},{"type":"anti_king"},{"type":"non_consecutive"},{"type":"even","cells":[[0,0]]}]"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Vec<Constraint>>(&json).unwrap(),
+            constraints
+        );
+    }
+
+    #[test]
+    fn test_thermometer() {
+        let thermo = Constraint::Thermometer {
+            cells: vec![(0, 0), (0, 1), (0, 2)],
+        };
+        let mut board = vec![vec![0; 4]; 4];
+
+        assert!(
+            !thermo.allows(&board, 0, 0, 3),
+            "Two cells above need 4 and 5"
+        );
+        assert!(
+            !thermo.allows(&board, 0, 2, 2),
+            "Two cells below need 1 and 2"
+        );
+        board[0][2] = 3;
+        assert!(thermo.allows(&board, 0, 1, 2));
+        assert!(!thermo.allows(&board, 0, 1, 3));
+        assert!(thermo.is_satisfied(&board));
+
+        board[0][0] = 3;
+        assert!(!thermo.is_satisfied(&board));
+    }
+
+    #[test]
+    fn test_arrow() {
+        let arrow = Constraint::Arrow {
+            circle: (0, 0),
+            cells: vec![(1, 1), (2, 2)],
+        };
+        let mut board = vec![vec![0; 4]; 4];
+
+        assert!(
+            !arrow.allows(&board, 0, 0, 1),
+            "Two cells add up to at least 2"
+        );
+        assert!(!arrow.allows(&board, 1, 1, 4), "The circle holds at most 4");
+        board[0][0] = 4;
+        assert!(
+            arrow.allows(&board, 1, 1, 2),
+            "Digits on an arrow may repeat"
+        );
+        assert!(!arrow.allows(&board, 1, 1, 4));
+
+        board[1][1] = 1;
+        board[2][2] = 2;
+        assert!(!arrow.is_satisfied(&board), "1 + 2 is not 4");
+        board[2][2] = 3;
+        assert!(arrow.is_satisfied(&board));
+    }
+
+    #[test]
+    fn test_kropki_dots() {
+        let white = Constraint::WhiteDot {
+            cells: [(0, 0), (0, 1)],
+        };
+        let black = Constraint::BlackDot {
+            cells: [(1, 0), (1, 1)],
+        };
+        let mut board = vec![vec![0; 4]; 4];
+        board[0][0] = 2;
+        board[1][0] = 2;
+
+        assert!(white.allows(&board, 0, 1, 3));
+        assert!(!white.allows(&board, 0, 1, 4));
+        assert!(black.allows(&board, 1, 1, 1));
+        assert!(black.allows(&board, 1, 1, 4));
+        assert!(!black.allows(&board, 1, 1, 3));
+
+        let shape = Shape::for_size(4).unwrap();
+        let apart = Constraint::WhiteDot {
+            cells: [(0, 0), (1, 1)],
+        };
+        assert!(Constraint::check_all(shape, &[white, black]).is_ok());
+        assert!(
+            Constraint::check_all(shape, &[apart]).is_err(),
+            "Dots join orthogonal neighbours"
+        );
+    }
+
+    #[test]
+    fn test_line_and_dot_json() {
+        let constraints = vec![
+            Constraint::Thermometer {
+                cells: vec![(0, 0), (0, 1)],
+            },
+            Constraint::Arrow {
+                circle: (2, 2),
+                cells: vec![(3, 3)],
+            },
+            Constraint::BlackDot {
+                cells: [(1, 0), (1, 1)],
+            },
+        ];
+        let json = serde_json::to_string(&constraints).unwrap();
+
+        assert_eq!(
+            json,
+            r#"[{"type":"thermometer","cells":[[0,0],[0,1]]},{"type":"arrow","circle":[2,2],"cells":[[3,3]]},{"type":"black_dot","cells":[[1,0],[1,1]]}]"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Vec<Constraint>>(&json).unwrap(),
+            constraints
+        );
+    }
+}
@@ -0,0 +1,489 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, anyhow};
+
+use crate::{
+    cell::{Cell, Digit},
+    shape::Shape,
+    solvers::{backtracking::BacktrackingSolver, solver::Solver},
+    sudoku::Sudoku,
+    validator::Validator,
+};
+
+// A change a player makes to one cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Move {
+    Place { cell: Cell, digit: Digit },
+    // Clears the digit and every candidate.
+    Erase { cell: Cell },
+    ToggleCandidate { cell: Cell, digit: Digit },
+}
+
+impl Move {
+    pub fn cell(self) -> Cell {
+        match self {
+            Self::Place { cell, .. }
+            | Self::Erase { cell }
+            | Self::ToggleCandidate { cell, .. } => cell,
+        }
+    }
+}
+
+// A move with what the cell held before it, so it can be taken back.
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    played: Move,
+    value: u8,
+    candidates: u32,
+}
+
+// A game in progress, independent of how it is shown: the board, candidates
+// the player has pencilled in, undo and redo history, mistakes counted
+// against the solution and a clock that stops once the board is complete.
+#[derive(Debug, Clone)]
+pub struct GameSession {
+    board: Sudoku,
+    solution: Sudoku,
+    // Bit `d` set when digit `d` is pencilled into the cell.
+    candidates: Vec<u32>,
+    undo: Vec<Entry>,
+    redo: Vec<Entry>,
+    mistakes: usize,
+    // Time played before the clock last started, and when that was.
+    elapsed: Duration,
+    running: Option<Instant>,
+}
+
+impl GameSession {
+    // Solves the puzzle up front to know the solution; fails if there is none.
+    pub fn new(puzzle: Sudoku) -> Result<Self> {
+        if !Validator::is_valid_board(&puzzle) {
+            return Err(anyhow!("Sudoku is invalid"));
+        }
+        let mut solution = puzzle.clone();
+        BacktrackingSolver::solve(&mut solution)?;
+        Self::with_solution(puzzle, solution)
+    }
+
+    pub fn with_solution(puzzle: Sudoku, solution: Sudoku) -> Result<Self> {
+        if solution.shape() != puzzle.shape()
+            || solution.rows().flatten().any(|&value| value == 0)
+            || !Validator::is_valid_board(&solution)
+        {
+            return Err(anyhow!("Solution is not a complete board for the puzzle"));
+        }
+        for row in 0..puzzle.size() {
+            for col in 0..puzzle.size() {
+                let value = puzzle[row][col];
+                if value != 0 && solution[row][col] != value {
+                    return Err(anyhow!(
+                        "Solution disagrees with the puzzle at r{}c{}",
+                        row + 1,
+                        col + 1
+                    ));
+                }
+            }
+        }
+
+        let cells = puzzle.shape().cell_count();
+        let mut session = Self {
+            board: puzzle,
+            solution,
+            candidates: vec![0; cells],
+            undo: Vec::new(),
+            redo: Vec::new(),
+            mistakes: 0,
+            elapsed: Duration::ZERO,
+            running: Some(Instant::now()),
+        };
+        session.stop_if_complete();
+        Ok(session)
+    }
+
+    pub fn board(&self) -> &Sudoku {
+        &self.board
+    }
+
+    pub fn solution(&self) -> &Sudoku {
+        &self.solution
+    }
+
+    pub fn candidates(&self, cell: Cell) -> impl Iterator<Item = Digit> + use<> {
+        let mask = if self.board.contains(cell) {
+            self.candidates[self.index_of(cell)]
+        } else {
+            0
+        };
+        (1..=Shape::MAX_SIZE as u8)
+            .filter(move |digit| mask & (1 << digit) != 0)
+            .filter_map(|digit| Digit::new(digit).ok())
+    }
+
+    // Every digit ever placed that the solution does not have there, undone
+    // or not.
+    pub fn mistakes(&self) -> usize {
+        self.mistakes
+    }
+
+    // Whether the cell holds a digit other than the solution's.
+    pub fn is_mistake(&self, cell: Cell) -> bool {
+        self.board
+            .get(cell)
+            .is_some_and(|digit| self.solution.get(cell) != Some(digit))
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.board.rows().flatten().all(|&value| value != 0)
+            && Validator::is_valid_board(&self.board)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed + self.running.map_or(Duration::ZERO, |start| start.elapsed())
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.running.is_none()
+    }
+
+    pub fn pause(&mut self) {
+        if let Some(start) = self.running.take() {
+            self.elapsed += start.elapsed();
+        }
+    }
+
+    // Completed games stay stopped.
+    pub fn resume(&mut self) {
+        if self.running.is_none() && !self.is_complete() {
+            self.running = Some(Instant::now());
+        }
+    }
+
+    // Moves played so far, oldest first, without those undone.
+    pub fn history(&self) -> impl Iterator<Item = Move> + '_ {
+        self.undo.iter().map(|entry| entry.played)
+    }
+
+    // Moves undone and not yet played again, in the order redo replays them.
+    pub fn undone(&self) -> impl Iterator<Item = Move> + '_ {
+        self.redo.iter().rev().map(|entry| entry.played)
+    }
+
+    // Carries over the mistakes and time of a saved game. The clock stays
+    // paused until `resume`.
+    pub(crate) fn restore_progress(&mut self, mistakes: usize, elapsed: Duration) {
+        self.mistakes = mistakes;
+        self.elapsed = elapsed;
+        self.running = None;
+    }
+
+    // Applies a move and clears the redo history. Moves that change nothing
+    // are not recorded.
+    pub fn play(&mut self, played: Move) -> Result<()> {
+        if self.is_complete() {
+            return Err(anyhow!("The puzzle is already solved"));
+        }
+        let cell = played.cell();
+        if !self.board.contains(cell) {
+            return Err(anyhow!("{} is off the board", cell));
+        }
+        if self.board.is_given(cell) {
+            return Err(anyhow!("{} is a given", cell));
+        }
+        if let Move::Place { digit, .. } | Move::ToggleCandidate { digit, .. } = played
+            && digit.get() as usize > self.board.size()
+        {
+            return Err(anyhow!("{} is not a digit on this board", digit));
+        }
+
+        let entry = self.perform(played)?;
+        if (entry.value, entry.candidates) == self.cell_state(cell) {
+            return Ok(());
+        }
+        if let Move::Place { .. } = played
+            && self.is_mistake(cell)
+        {
+            self.mistakes += 1;
+        }
+        self.undo.push(entry);
+        self.redo.clear();
+        self.stop_if_complete();
+        Ok(())
+    }
+
+    // Takes back the last move, returning it. Taking a completed board back
+    // to incomplete restarts the clock; otherwise it is left as it was.
+    pub fn undo(&mut self) -> Option<Move> {
+        let was_complete = self.is_complete();
+        let entry = self.undo.pop()?;
+        let cell = entry.played.cell();
+        let index = self.index_of(cell);
+        self.write(cell, entry.value);
+        self.candidates[index] = entry.candidates;
+        self.redo.push(entry);
+        if was_complete {
+            self.resume();
+        }
+        Some(entry.played)
+    }
+
+    // Plays the last undone move again, returning it. A repeated mistake is
+    // not counted twice.
+    pub fn redo(&mut self) -> Option<Move> {
+        let entry = self.redo.pop()?;
+        let entry = self
+            .perform(entry.played)
+            .expect("Undone moves were legal when played");
+        self.undo.push(entry);
+        self.stop_if_complete();
+        Some(entry.played)
+    }
+
+    fn perform(&mut self, played: Move) -> Result<Entry> {
+        let cell = played.cell();
+        let (value, candidates) = self.cell_state(cell);
+        let index = self.index_of(cell);
+        match played {
+            Move::Place { digit, .. } => self.board.set(cell, digit)?,
+            Move::Erase { .. } => {
+                self.board.clear(cell)?;
+                self.candidates[index] = 0;
+            }
+            Move::ToggleCandidate { digit, .. } => self.candidates[index] ^= 1 << digit.get(),
+        }
+        Ok(Entry {
+            played,
+            value,
+            candidates,
+        })
+    }
+
+    fn write(&mut self, cell: Cell, value: u8) {
+        let written = match Digit::new(value) {
+            Ok(digit) => self.board.set(cell, digit),
+            Err(_) => self.board.clear(cell),
+        };
+        written.expect("Moves only touch cells that are not givens");
+    }
+
+    fn cell_state(&self, cell: Cell) -> (u8, u32) {
+        (
+            self.board[cell.row()][cell.col()],
+            self.candidates[self.index_of(cell)],
+        )
+    }
+
+    fn index_of(&self, cell: Cell) -> usize {
+        cell.row() * self.board.size() + cell.col()
+    }
+
+    fn stop_if_complete(&mut self) {
+        if self.is_complete() {
+            self.pause();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    fn session() -> GameSession {
+        GameSession::new(Sudoku::from_string(PUZZLE).unwrap()).unwrap()
+    }
+
+    fn cell(row: usize, col: usize) -> Cell {
+        Cell::new(row, col).unwrap()
+    }
+
+    fn digit(value: u8) -> Digit {
+        Digit::new(value).unwrap()
+    }
+
+    #[test]
+    fn test_new_solves_the_puzzle() {
+        let session = session();
+
+        assert_eq!(session.solution().to_string(), SOLUTION);
+        assert!(!session.is_complete());
+        let stuck = Sudoku::from_string("1200004000030000").unwrap();
+        assert!(
+            GameSession::new(stuck).is_err(),
+            "Nothing fits in r1c4, so there is no solution"
+        );
+    }
+
+    #[test]
+    fn test_with_solution_must_match_the_puzzle() {
+        let puzzle = Sudoku::from_string(PUZZLE).unwrap();
+        let solution = Sudoku::from_string(SOLUTION).unwrap();
+
+        assert!(GameSession::with_solution(puzzle.clone(), solution.clone()).is_ok());
+        assert!(GameSession::with_solution(puzzle.clone(), puzzle.clone()).is_err());
+        let other = Sudoku::from_string(
+            &SOLUTION
+                .replace('1', "x")
+                .replace('2', "1")
+                .replace('x', "2"),
+        )
+        .unwrap();
+        assert!(
+            GameSession::with_solution(puzzle, other).is_err(),
+            "Swapping 1 and 2 clashes with the givens"
+        );
+    }
+
+    #[test]
+    fn test_moves_undo_and_redo() {
+        let mut session = session();
+
+        session
+            .play(Move::ToggleCandidate {
+                cell: cell(0, 2),
+                digit: digit(4),
+            })
+            .unwrap();
+        session
+            .play(Move::Place {
+                cell: cell(0, 2),
+                digit: digit(4),
+            })
+            .unwrap();
+        assert_eq!(session.board()[0][2], 4);
+        assert_eq!(
+            session.candidates(cell(0, 2)).collect::<Vec<_>>(),
+            [digit(4)]
+        );
+
+        session.play(Move::Erase { cell: cell(0, 2) }).unwrap();
+        assert_eq!(session.board()[0][2], 0);
+        assert_eq!(session.candidates(cell(0, 2)).count(), 0);
+        assert_eq!(session.history().count(), 3);
+
+        assert_eq!(session.undo(), Some(Move::Erase { cell: cell(0, 2) }));
+        assert_eq!(session.board()[0][2], 4);
+        assert!(session.undo().is_some());
+        assert!(session.undo().is_some());
+        assert_eq!(session.undo(), None);
+        assert_eq!(session.candidates(cell(0, 2)).count(), 0);
+
+        assert!(session.redo().is_some());
+        assert_eq!(session.candidates(cell(0, 2)).count(), 1);
+        session.play(Move::Erase { cell: cell(1, 1) }).unwrap();
+        assert_eq!(
+            session.history().count(),
+            1,
+            "Erasing an empty cell is no move"
+        );
+        assert_eq!(
+            session.undone().collect::<Vec<_>>(),
+            [
+                Move::Place {
+                    cell: cell(0, 2),
+                    digit: digit(4)
+                },
+                Move::Erase { cell: cell(0, 2) }
+            ],
+            "Nothing changed, so redo survives"
+        );
+    }
+
+    #[test]
+    fn test_illegal_moves_are_rejected() {
+        let mut session = session();
+
+        assert!(
+            session.play(Move::Erase { cell: cell(0, 0) }).is_err(),
+            "Given"
+        );
+        assert!(session.play(Move::Erase { cell: cell(9, 0) }).is_err());
+        let small = Sudoku::from_string(&"0".repeat(16)).unwrap();
+        let mut small = GameSession::new(small).unwrap();
+        assert!(
+            small
+                .play(Move::Place {
+                    cell: cell(0, 0),
+                    digit: digit(5)
+                })
+                .is_err()
+        );
+        assert_eq!(session.history().count(), 0);
+    }
+
+    #[test]
+    fn test_mistakes_are_counted_against_the_solution() {
+        let mut session = session();
+        let wrong = Move::Place {
+            cell: cell(0, 2),
+            digit: digit(1),
+        };
+
+        session.play(wrong).unwrap();
+        assert!(session.is_mistake(cell(0, 2)));
+        assert_eq!(session.mistakes(), 1);
+        session.undo();
+        session.redo();
+        assert_eq!(session.mistakes(), 1, "Redo does not count again");
+
+        session
+            .play(Move::Place {
+                cell: cell(0, 2),
+                digit: digit(4),
+            })
+            .unwrap();
+        assert!(!session.is_mistake(cell(0, 2)));
+        assert_eq!(session.mistakes(), 1);
+    }
+
+    #[test]
+    fn test_completion_stops_the_clock() {
+        let mut almost = SOLUTION.to_string();
+        almost.replace_range(80..81, "0");
+        let mut session = GameSession::new(Sudoku::from_string(&almost).unwrap()).unwrap();
+        let last = Move::Place {
+            cell: cell(8, 8),
+            digit: digit(9),
+        };
+
+        session.play(last).unwrap();
+        assert!(session.is_complete());
+        assert!(session.is_paused());
+        let elapsed = session.elapsed();
+        session.resume();
+        assert_eq!(session.elapsed(), elapsed, "Completed games stay stopped");
+        assert!(session.play(Move::Erase { cell: cell(8, 8) }).is_err());
+
+        session.undo();
+        assert!(
+            !session.is_paused(),
+            "Undoing the last digit restarts the clock"
+        );
+        session.pause();
+        let elapsed = session.elapsed();
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(session.elapsed(), elapsed, "Paused");
+    }
+
+    #[test]
+    fn test_undo_keeps_a_paused_clock_paused() {
+        let mut session = session();
+        session
+            .play(Move::Place {
+                cell: cell(0, 2),
+                digit: digit(4),
+            })
+            .unwrap();
+
+        session.pause();
+        session.undo();
+        assert!(session.is_paused());
+        session.redo();
+        assert!(session.is_paused());
+    }
+}
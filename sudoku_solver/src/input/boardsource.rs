@@ -2,7 +2,9 @@ use anyhow::Result;
 
 use crate::sudoku::Sudoku;
 
-use super::{csv_handler::CsvHandler, json_handler::JsonHandler};
+use super::{
+    csv_handler::CsvHandler, json_handler::JsonHandler, pencilmark_handler::PencilMarkHandler,
+};
 
 // pub trait BoardSource {
 //     fn load_from_file(path: &str) -> Result<Vec<Sudoku>>;
@@ -12,6 +14,9 @@ use super::{csv_handler::CsvHandler, json_handler::JsonHandler};
 pub enum FileFormat {
     Json,
     Csv,
+    // A candidate string or pencil-mark grid; cells with a single candidate
+    // become the clues.
+    PencilMarks,
     // Xml,
 }
 
@@ -19,6 +24,7 @@ pub fn load_boards_by_format(format: FileFormat, path: &str) -> Result<Vec<Sudok
     match format {
         FileFormat::Json => JsonHandler::load_from_file(path),
         FileFormat::Csv => CsvHandler::load_from_file(path),
+        FileFormat::PencilMarks => Ok(vec![PencilMarkHandler::load_from_file(path)?.to_sudoku()?]),
         // FileFormat::Xml => XmlHandler::load_from_file(path),
     }
 }
@@ -28,6 +34,8 @@ pub fn detect_format_from_path(path: &str) -> Option<FileFormat> {
         Some(FileFormat::Json)
     } else if path.ends_with(".csv") {
         Some(FileFormat::Csv)
+    } else if path.ends_with(".txt") {
+        Some(FileFormat::PencilMarks)
     }
     // else if path.ends_with(".xml") {
     // Some(FileFormat::Xml)
@@ -1,4 +1,4 @@
-use crate::sudoku::Sudoku;
+use crate::{regions::Regions, sudoku::Sudoku};
 use anyhow::{Context, Result};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
@@ -11,29 +11,47 @@ impl CsvHandler {
             File::open(path).with_context(|| format!("Failed to open CSV file: {}", path))?;
         let reader = BufReader::new(file);
 
-        let board: Vec<Vec<u8>> = reader
-            .lines()
-            .enumerate()
-            .map(|(i, line)| {
-                let line = line?;
-                let row: Vec<u8> = line
-                    .split(',')
-                    .map(str::trim)
-                    .map(str::parse::<u8>)
-                    .collect::<Result<_, _>>()
-                    .with_context(|| format!("Invalid number in line {}", i + 1))?;
+        // A blank line separates the board from an optional jigsaw region map.
+        let mut blocks: Vec<Vec<(usize, Vec<u8>)>> = vec![Vec::new()];
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                blocks.push(Vec::new());
+                continue;
+            }
+            let row: Vec<u8> = line
+                .split(',')
+                .map(str::trim)
+                .map(str::parse::<u8>)
+                .collect::<Result<_, _>>()
+                .with_context(|| format!("Invalid number in line {}", i + 1))?;
+            blocks.last_mut().unwrap().push((i, row));
+        }
+        blocks.retain(|block| !block.is_empty());
 
-                if row.len() != 9 {
-                    anyhow::bail!("Line {} does not contain 9 values", i + 1);
-                }
+        let [board, regions @ ..] = blocks.as_slice() else {
+            anyhow::bail!("CSV does not contain a board");
+        };
+        if regions.len() > 1 {
+            anyhow::bail!("CSV contains more than a board and a region map");
+        }
 
-                Ok(row)
-            })
-            .collect::<Result<_>>()?;
-        if board.len() != 9 {
-            return Err(anyhow::anyhow!("CSV does not contain 9 rows"));
+        // The number of rows gives the board size.
+        let size = board.len();
+        for (i, row) in board.iter().chain(regions.iter().flatten()) {
+            if row.len() != size {
+                anyhow::bail!("Line {} does not contain {} values", i + 1, size);
+            }
         }
 
-        Ok(vec![Sudoku::new(board)?])
+        let rows = |block: &[(usize, Vec<u8>)]| block.iter().map(|(_, row)| row.clone()).collect();
+        let sudoku = match regions.first() {
+            Some(regions) => {
+                let regions: Vec<Vec<u8>> = rows(regions);
+                Sudoku::jigsaw(rows(board), Regions::new(&regions)?)?
+            }
+            None => Sudoku::new(rows(board))?,
+        };
+        Ok(vec![sudoku])
     }
 }
@@ -1,7 +1,7 @@
 use crate::sudoku::Sudoku;
 use anyhow::{Result, anyhow};
 use serde_json;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Read;
 
 pub struct JsonHandler;
@@ -18,6 +18,12 @@ impl JsonHandler {
 
         Ok(sudoku_boards)
     }
+
+    pub fn save_to_file(path: &str, boards: &[Sudoku]) -> Result<()> {
+        let contents = serde_json::to_string_pretty(boards)
+            .map_err(|err| anyhow!("Failed to serialize JSON: {}", err))?;
+        fs::write(path, contents).map_err(|err| anyhow!("Failed to write the file: {}", err))
+    }
 }
 
 pub fn read_file(file_path: &str) -> Result<String> {
@@ -33,6 +39,7 @@ pub fn read_file(file_path: &str) -> Result<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::constraint::Constraint;
     use std::fs::{File, remove_file};
     use std::io::Write;
 
@@ -161,4 +168,21 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_save_and_load_constraints() {
+        let path = "test_constraints.json";
+        let sudoku = Sudoku::from_string(&"0".repeat(81))
+            .unwrap()
+            .with_constraints(vec![Constraint::Diagonals])
+            .unwrap();
+
+        JsonHandler::save_to_file(path, &[sudoku]).unwrap();
+        let contents = fs::read_to_string(path).unwrap();
+        let loaded = JsonHandler::load_from_file(path);
+        remove_file(path).unwrap();
+
+        assert!(contents.contains(r#""type": "diagonals""#));
+        assert_eq!(loaded.unwrap()[0].constraints(), [Constraint::Diagonals]);
+    }
 }
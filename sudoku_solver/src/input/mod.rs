@@ -1,3 +1,5 @@
 pub mod boardsource;
 pub mod csv_handler;
 pub mod json_handler;
+pub mod pencilmark_handler;
+pub mod savegame;
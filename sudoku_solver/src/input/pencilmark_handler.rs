@@ -0,0 +1,277 @@
+use crate::{candidate_grid::CandidateGrid, shape::Shape};
+use anyhow::{Context, Result, anyhow};
+use std::fs;
+
+use super::json_handler::read_file;
+
+pub struct PencilMarkHandler;
+
+impl PencilMarkHandler {
+    pub fn load_from_file(path: &str) -> Result<CandidateGrid> {
+        Self::parse(&read_file(path)?)
+    }
+
+    pub fn save_to_file(path: &str, grid: &CandidateGrid) -> Result<()> {
+        fs::write(path, Self::to_grid(grid))
+            .with_context(|| format!("Failed to write pencil marks: {}", path))
+    }
+
+    // Accepts either a candidate string (729 characters for a 9x9 board) or a
+    // pencil-mark grid.
+    pub fn parse(text: &str) -> Result<CandidateGrid> {
+        let compact: String = text.split_whitespace().collect();
+        let is_candidate_string = (4..=Shape::MAX_SIZE).any(|size| compact.len() == size.pow(3))
+            && compact.chars().all(|c| Shape::parse_symbol(c).is_some());
+        if is_candidate_string {
+            Self::parse_candidate_string(&compact)
+        } else {
+            Self::parse_grid(text)
+        }
+    }
+
+    // `size` candidates per cell, each either its own symbol or `.`/`0`.
+    pub fn parse_candidate_string(text: &str) -> Result<CandidateGrid> {
+        let chars: Vec<char> = text.trim().chars().collect();
+        let shape = (4..=Shape::MAX_SIZE)
+            .find(|size| size.pow(3) == chars.len())
+            .and_then(|size| Shape::for_size(size).ok())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Candidate string must contain size^3 characters (729 for 9x9), found {}",
+                    chars.len()
+                )
+            })?;
+
+        let mut masks = vec![0; shape.cell_count()];
+        for (cell, chunk) in chars.chunks(shape.size()).enumerate() {
+            for (i, &c) in chunk.iter().enumerate() {
+                let digit = i as u8 + 1;
+                match Shape::parse_symbol(c) {
+                    Some(0) => {}
+                    Some(value) if value == digit => masks[cell] |= 1 << digit,
+                    _ => return Err(anyhow!("Invalid candidate '{}' in cell {}", c, cell + 1)),
+                }
+            }
+        }
+
+        CandidateGrid::from_candidates(shape, &masks)
+    }
+
+    // Every run of symbols is one cell; borders and separators are ignored.
+    pub fn parse_grid(text: &str) -> Result<CandidateGrid> {
+        let tokens: Vec<&str> = text
+            .split(|c: char| c.is_whitespace() || c == '|')
+            .filter(|token| !token.is_empty() && token.chars().all(|c| c.is_ascii_alphanumeric()))
+            .collect();
+        let shape = (4..=Shape::MAX_SIZE)
+            .find(|size| size * size == tokens.len())
+            .and_then(|size| Shape::for_size(size).ok())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Pencil-mark grid must contain a square number of cells (81 for 9x9), found {}",
+                    tokens.len()
+                )
+            })?;
+
+        let mut masks = vec![0; shape.cell_count()];
+        for (cell, token) in tokens.iter().enumerate() {
+            for c in token.chars() {
+                match Shape::parse_symbol(c) {
+                    Some(digit) if digit >= 1 && digit as usize <= shape.size() => {
+                        masks[cell] |= 1 << digit
+                    }
+                    _ => return Err(anyhow!("Invalid candidate '{}' in cell {}", c, cell + 1)),
+                }
+            }
+        }
+
+        CandidateGrid::from_candidates(shape, &masks)
+    }
+
+    pub fn to_candidate_string(grid: &CandidateGrid) -> String {
+        (0..grid.cell_count())
+            .flat_map(|cell| {
+                let mask = cell_mask(grid, cell);
+                grid.all_digits().map(move |digit| {
+                    if mask & (1 << digit) != 0 {
+                        Shape::symbol(digit)
+                    } else {
+                        '.'
+                    }
+                })
+            })
+            .collect()
+    }
+
+    pub fn to_grid(grid: &CandidateGrid) -> String {
+        let size = grid.size();
+        let box_cols = grid.shape().box_cols();
+        let cells: Vec<String> = (0..grid.cell_count())
+            .map(|cell| {
+                let mask = cell_mask(grid, cell);
+                grid.all_digits()
+                    .filter(|digit| mask & (1 << digit) != 0)
+                    .map(Shape::symbol)
+                    .collect()
+            })
+            .collect();
+        let widths: Vec<usize> = (0..size)
+            .map(|col| {
+                (0..size)
+                    .map(|row| cells[row * size + col].len())
+                    .max()
+                    .unwrap_or(1)
+            })
+            .collect();
+
+        let separator = widths
+            .chunks(box_cols)
+            .map(|box_widths| "-".repeat(box_widths.iter().sum::<usize>() + box_cols + 1))
+            .collect::<Vec<_>>()
+            .join("+");
+        let separator = format!("+{}+\n", separator);
+
+        let mut output = separator.clone();
+        for row in 0..size {
+            let boxes: Vec<String> = (0..size / box_cols)
+                .map(|b| {
+                    (b * box_cols..(b + 1) * box_cols)
+                        .map(|col| {
+                            format!("{:<width$}", cells[row * size + col], width = widths[col])
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect();
+            output.push_str(&format!("| {} |\n", boxes.join(" | ")));
+            if row % grid.shape().box_rows() == grid.shape().box_rows() - 1 {
+                output.push_str(&separator);
+            }
+        }
+        output
+    }
+}
+
+fn cell_mask(grid: &CandidateGrid, cell: usize) -> u32 {
+    match grid.value(cell) {
+        0 => grid.candidates(cell),
+        value => 1 << value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::Sudoku;
+
+    fn wikipedia_grid() -> CandidateGrid {
+        let sudoku = Sudoku::from_string(
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079",
+        )
+        .unwrap();
+        CandidateGrid::from_sudoku(&sudoku)
+    }
+
+    // Single candidates read back as placed digits, so settle them first.
+    fn settled_grid() -> CandidateGrid {
+        let mut grid = wikipedia_grid();
+        while let Some(cell) = (0..81).find(|&cell| grid.candidates(cell).count_ones() == 1) {
+            let digit = grid.candidate_digits(cell).next().unwrap();
+            grid.place(cell, digit);
+        }
+        grid
+    }
+
+    #[test]
+    fn test_candidate_string_round_trip() {
+        let mut grid = settled_grid();
+        grid.eliminate(2, 4);
+
+        let text = PencilMarkHandler::to_candidate_string(&grid);
+        assert_eq!(text.len(), 729);
+        assert_eq!(&text[..18], "....5......3......");
+
+        let parsed = PencilMarkHandler::parse_candidate_string(&text).unwrap();
+        assert_eq!(parsed, grid);
+    }
+
+    #[test]
+    fn test_grid_round_trip() {
+        let mut grid = settled_grid();
+        grid.eliminate(2, 4);
+
+        let text = PencilMarkHandler::to_grid(&grid);
+        assert!(text.starts_with("+---"));
+        assert!(text.lines().nth(1).unwrap().starts_with("| 5 "));
+
+        let parsed = PencilMarkHandler::parse(&text).unwrap();
+        assert_eq!(parsed, grid);
+    }
+
+    #[test]
+    fn test_load_from_file() {
+        let grid = PencilMarkHandler::load_from_file("inputs/pencilmarks.txt").unwrap();
+
+        assert_eq!(grid.value(0), 5);
+        assert_eq!(grid.value(40), 5, "Single candidate should be placed");
+        assert!(
+            !grid.has_candidate(37, 5),
+            "Placed digit should leave its peers"
+        );
+        assert_eq!(grid.candidate_digits(2).collect::<Vec<_>>(), vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn test_save_to_file() {
+        let path = "test_pencilmarks.txt";
+        let grid = settled_grid();
+
+        PencilMarkHandler::save_to_file(path, &grid).unwrap();
+        let loaded = PencilMarkHandler::load_from_file(path);
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(loaded.unwrap(), grid);
+    }
+
+    #[test]
+    fn test_parse_grid_with_wrong_cell_count() {
+        let result = PencilMarkHandler::parse_grid("| 12 3 | 456 |");
+
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("must contain a square number of cells"),
+            "Unexpected error message"
+        );
+    }
+
+    #[test]
+    fn test_parse_candidate_string_with_misplaced_digit() {
+        let text = "2".repeat(729);
+
+        assert!(PencilMarkHandler::parse_candidate_string(&text).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_conflicting_placements() {
+        let mut text = PencilMarkHandler::to_candidate_string(&wikipedia_grid());
+        text.replace_range(9..18, "....5....");
+
+        assert!(PencilMarkHandler::parse_candidate_string(&text).is_err());
+    }
+
+    #[test]
+    fn test_four_by_four_round_trip() {
+        let grid = CandidateGrid::from_sudoku(&Sudoku::from_string("1000001000000000").unwrap());
+
+        let text = PencilMarkHandler::to_candidate_string(&grid);
+        assert_eq!(text.len(), 64);
+        assert_eq!(PencilMarkHandler::parse(&text).unwrap(), grid);
+        assert_eq!(
+            PencilMarkHandler::parse(&PencilMarkHandler::to_grid(&grid)).unwrap(),
+            grid
+        );
+    }
+}
@@ -0,0 +1,404 @@
+use std::{fs, time::Duration};
+
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::json_handler::read_file;
+use crate::{
+    cell::{Cell, Digit},
+    game::{GameSession, Move},
+    sudoku::Sudoku,
+    validator::Validator,
+};
+
+// Saves written now carry this version. Loading accepts it and every older
+// one; fields added within a version default when missing, and unknown
+// fields are ignored.
+pub const SAVE_VERSION: u64 = 1;
+
+pub struct SaveGameHandler;
+
+// Version 1. The board and candidates are what the history produces; they
+// are kept so the save can be read on its own, and checked on load.
+#[derive(Serialize, Deserialize)]
+struct SaveGame {
+    version: u64,
+    puzzle: Sudoku,
+    solution: Vec<Vec<u8>>,
+    board: Vec<Vec<u8>>,
+    #[serde(default)]
+    candidates: Vec<Candidates>,
+    #[serde(default)]
+    history: Vec<SavedMove>,
+    #[serde(default)]
+    undone: Vec<SavedMove>,
+    #[serde(default)]
+    mistakes: usize,
+    #[serde(default)]
+    elapsed_ms: u64,
+}
+
+// The pencil marks of one cell.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Candidates {
+    cell: (usize, usize),
+    digits: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SavedMove {
+    Place { cell: (usize, usize), digit: u8 },
+    Erase { cell: (usize, usize) },
+    ToggleCandidate { cell: (usize, usize), digit: u8 },
+}
+
+impl From<Move> for SavedMove {
+    fn from(played: Move) -> Self {
+        let cell = (played.cell().row(), played.cell().col());
+        match played {
+            Move::Place { digit, .. } => Self::Place {
+                cell,
+                digit: digit.get(),
+            },
+            Move::Erase { .. } => Self::Erase { cell },
+            Move::ToggleCandidate { digit, .. } => Self::ToggleCandidate {
+                cell,
+                digit: digit.get(),
+            },
+        }
+    }
+}
+
+impl TryFrom<SavedMove> for Move {
+    type Error = anyhow::Error;
+
+    fn try_from(saved: SavedMove) -> Result<Self> {
+        Ok(match saved {
+            SavedMove::Place { cell, digit } => Self::Place {
+                cell: Cell::new(cell.0, cell.1)?,
+                digit: Digit::new(digit)?,
+            },
+            SavedMove::Erase { cell } => Self::Erase {
+                cell: Cell::new(cell.0, cell.1)?,
+            },
+            SavedMove::ToggleCandidate { cell, digit } => Self::ToggleCandidate {
+                cell: Cell::new(cell.0, cell.1)?,
+                digit: Digit::new(digit)?,
+            },
+        })
+    }
+}
+
+impl SaveGameHandler {
+    pub fn load_from_file(path: &str) -> Result<GameSession> {
+        Self::from_json(&read_file(path)?)
+    }
+
+    pub fn save_to_file(path: &str, session: &GameSession) -> Result<()> {
+        fs::write(path, Self::to_json(session)?)
+            .with_context(|| format!("Failed to write the save game: {}", path))
+    }
+
+    pub fn to_json(session: &GameSession) -> Result<String> {
+        let save = SaveGame {
+            version: SAVE_VERSION,
+            puzzle: session.board().puzzle(),
+            solution: session.solution().rows().map(<[u8]>::to_vec).collect(),
+            board: session.board().rows().map(<[u8]>::to_vec).collect(),
+            candidates: candidates(session),
+            history: session.history().map(SavedMove::from).collect(),
+            undone: session.undone().map(SavedMove::from).collect(),
+            mistakes: session.mistakes(),
+            elapsed_ms: session.elapsed().as_millis() as u64,
+        };
+        serde_json::to_string_pretty(&save)
+            .map_err(|err| anyhow!("Failed to serialize JSON: {}", err))
+    }
+
+    // Replays the saved moves on the puzzle. The session comes back paused.
+    pub fn from_json(text: &str) -> Result<GameSession> {
+        let value: Value =
+            serde_json::from_str(text).map_err(|err| anyhow!("Failed to parse JSON: {}", err))?;
+        let version = value
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| anyhow!("Save game has no version"))?;
+        // Older layouts get an arm here that converts them to the current one.
+        let save: SaveGame = match version {
+            1 => serde_json::from_value(value)
+                .map_err(|err| anyhow!("Failed to parse the save game: {}", err))?,
+            _ => {
+                return Err(anyhow!(
+                    "Unsupported save game version {} (the latest is {})",
+                    version,
+                    SAVE_VERSION
+                ));
+            }
+        };
+        save.into_session()
+    }
+}
+
+impl SaveGame {
+    fn into_session(self) -> Result<GameSession> {
+        let solution = solution_for(&self.puzzle, &self.solution)?;
+        let mut session = GameSession::with_solution(self.puzzle, solution)?;
+
+        let undone = self.undone.len();
+        for saved in self.history.into_iter().chain(self.undone) {
+            session.play(Move::try_from(saved)?)?;
+        }
+        for _ in 0..undone {
+            session.undo();
+        }
+
+        let board: Vec<Vec<u8>> = session.board().rows().map(<[u8]>::to_vec).collect();
+        if board != self.board || candidates(&session) != self.candidates {
+            return Err(anyhow!("Save game board does not match its move history"));
+        }
+        session.restore_progress(self.mistakes, Duration::from_millis(self.elapsed_ms));
+        Ok(session)
+    }
+}
+
+// Fills the puzzle in from the saved solution, so the solution is checked
+// against the givens and against the puzzle's regions and constraints.
+fn solution_for(puzzle: &Sudoku, rows: &[Vec<u8>]) -> Result<Sudoku> {
+    let size = puzzle.size();
+    if rows.len() != size || rows.iter().any(|row| row.len() != size) {
+        return Err(anyhow!("Save game solution does not fit the puzzle"));
+    }
+
+    let mut solution = puzzle.clone();
+    for (row, values) in rows.iter().enumerate() {
+        for (col, &value) in values.iter().enumerate() {
+            let cell = Cell::new(row, col)?;
+            if puzzle.is_given(cell) {
+                if puzzle[row][col] != value {
+                    return Err(anyhow!(
+                        "Save game solution disagrees with the puzzle at {}",
+                        cell
+                    ));
+                }
+                continue;
+            }
+            let digit = Digit::new(value)
+                .map_err(|_| anyhow!("Save game solution is not complete at {}", cell))?;
+            solution.set(cell, digit)?;
+        }
+    }
+
+    if !Validator::is_valid_board(&solution) {
+        return Err(anyhow!("Save game solution breaks the rules of the puzzle"));
+    }
+    Ok(solution)
+}
+
+fn candidates(session: &GameSession) -> Vec<Candidates> {
+    let size = session.board().size();
+    (0..size * size)
+        .filter_map(|index| {
+            let cell = Cell::new(index / size, index % size).ok()?;
+            let digits: Vec<u8> = session.candidates(cell).map(Digit::get).collect();
+            (!digits.is_empty()).then_some(Candidates {
+                cell: (cell.row(), cell.col()),
+                digits,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::remove_file;
+
+    use super::*;
+    use crate::constraint::Constraint;
+
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    fn cell(row: usize, col: usize) -> Cell {
+        Cell::new(row, col).unwrap()
+    }
+
+    fn digit(value: u8) -> Digit {
+        Digit::new(value).unwrap()
+    }
+
+    fn session() -> GameSession {
+        let mut session = GameSession::new(Sudoku::from_string(PUZZLE).unwrap()).unwrap();
+        for played in [
+            Move::ToggleCandidate {
+                cell: cell(0, 2),
+                digit: digit(1),
+            },
+            Move::ToggleCandidate {
+                cell: cell(0, 2),
+                digit: digit(4),
+            },
+            Move::Place {
+                cell: cell(0, 3),
+                digit: digit(1),
+            },
+            Move::Place {
+                cell: cell(0, 3),
+                digit: digit(6),
+            },
+            Move::Place {
+                cell: cell(8, 0),
+                digit: digit(3),
+            },
+        ] {
+            session.play(played).unwrap();
+        }
+        session.undo();
+        session
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let session = session();
+        let json = SaveGameHandler::to_json(&session).unwrap();
+        assert!(json.contains(r#""version": 1"#));
+        assert!(json.contains(r#""type": "toggle_candidate""#));
+
+        let loaded = SaveGameHandler::from_json(&json).unwrap();
+        assert_eq!(loaded.board(), session.board());
+        assert_eq!(loaded.board().puzzle(), session.board().puzzle());
+        assert_eq!(
+            loaded.history().collect::<Vec<_>>(),
+            session.history().collect::<Vec<_>>()
+        );
+        assert_eq!(loaded.undone().count(), 1);
+        assert_eq!(
+            loaded.candidates(cell(0, 2)).collect::<Vec<_>>(),
+            [digit(1), digit(4)]
+        );
+        assert_eq!(loaded.mistakes(), 1, "The 1 in r1c4 was a mistake");
+        assert!(loaded.is_paused());
+        assert!(loaded.elapsed() <= session.elapsed());
+        assert!(!loaded.board().is_given(cell(0, 3)), "Entries stay entries");
+    }
+
+    #[test]
+    fn test_versions() {
+        let json = SaveGameHandler::to_json(&session()).unwrap();
+        let mut value: Value = serde_json::from_str(&json).unwrap();
+
+        value["added_later"] = Value::from("ignored");
+        assert!(SaveGameHandler::from_json(&value.to_string()).is_ok());
+
+        value["version"] = Value::from(SAVE_VERSION + 1);
+        let error = SaveGameHandler::from_json(&value.to_string()).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Unsupported save game version")
+        );
+
+        value.as_object_mut().unwrap().remove("version");
+        assert!(SaveGameHandler::from_json(&value.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_minimal_save_loads() {
+        let puzzle = Sudoku::from_string(PUZZLE).unwrap();
+        let rows = |board: &Sudoku| board.rows().map(<[u8]>::to_vec).collect::<Vec<_>>();
+        let json = serde_json::json!({
+            "version": 1,
+            "puzzle": puzzle,
+            "solution": rows(session().solution()),
+            "board": rows(&puzzle),
+        })
+        .to_string();
+
+        let loaded = SaveGameHandler::from_json(&json).unwrap();
+        assert_eq!(loaded.history().count(), 0);
+        assert_eq!(loaded.elapsed(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_tampered_board_is_rejected() {
+        let json = SaveGameHandler::to_json(&session()).unwrap();
+        let mut value: Value = serde_json::from_str(&json).unwrap();
+        value["board"][0][2] = Value::from(4);
+
+        let error = SaveGameHandler::from_json(&value.to_string()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Save game board does not match its move history"
+        );
+    }
+
+    #[test]
+    fn test_tampered_solution_is_rejected() {
+        let json = SaveGameHandler::to_json(&session()).unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        let load = |edit: &dyn Fn(&mut Value)| {
+            let mut value = value.clone();
+            edit(&mut value);
+            SaveGameHandler::from_json(&value.to_string())
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            load(&|value| value["solution"][0][0] = Value::from(4)),
+            "Save game solution disagrees with the puzzle at r1c1"
+        );
+        assert_eq!(
+            load(&|value| value["solution"][0][2] = Value::from(0)),
+            "Save game solution is not complete at r1c3"
+        );
+        assert_eq!(
+            load(&|value| value["solution"][0][2] = Value::from(2)),
+            "Save game solution breaks the rules of the puzzle"
+        );
+        assert_eq!(
+            load(&|value| {
+                value["solution"].as_array_mut().unwrap().pop();
+            }),
+            "Save game solution does not fit the puzzle"
+        );
+    }
+
+    #[test]
+    fn test_solution_must_keep_the_puzzle_constraints() {
+        let puzzle = Sudoku::from_string("1200340000000000")
+            .unwrap()
+            .with_constraints(vec![Constraint::Cage {
+                sum: 7,
+                cells: vec![(2, 0), (3, 0)],
+            }])
+            .unwrap();
+        // A valid classic 4x4 grid, but r3c1 + r4c1 is 2 + 4.
+        let json = serde_json::json!({
+            "version": 1,
+            "puzzle": puzzle,
+            "solution": [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]],
+            "board": puzzle.rows().map(<[u8]>::to_vec).collect::<Vec<_>>(),
+        })
+        .to_string();
+
+        let error = SaveGameHandler::from_json(&json).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Save game solution breaks the rules of the puzzle"
+        );
+    }
+
+    #[test]
+    fn test_save_and_load_file() {
+        let path = "test_save_game.json";
+        let session = session();
+
+        SaveGameHandler::save_to_file(path, &session).unwrap();
+        let loaded = SaveGameHandler::load_from_file(path);
+        remove_file(path).unwrap();
+
+        assert_eq!(loaded.unwrap().board(), session.board());
+    }
+}
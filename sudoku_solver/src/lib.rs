@@ -0,0 +1,19 @@
+pub mod batch;
+pub mod candidate_grid;
+pub mod cell;
+pub mod constraint;
+pub mod game;
+pub mod input;
+pub mod multigrid;
+pub mod regions;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod shape;
+pub mod solvers;
+pub mod sudoku;
+pub mod symmetry;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod validator;
+#[cfg(feature = "wasm-bindgen")]
+pub mod wasm;
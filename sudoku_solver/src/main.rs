@@ -1,15 +1,15 @@
-mod input;
-mod solvers;
-mod sudoku;
-mod validator;
+use std::env;
+
 use anyhow::Result;
-use input::boardsource::{detect_format_from_path, load_boards_by_format};
-use solvers::{
+use sudoku_solver::batch::{self, Dedup};
+use sudoku_solver::input::boardsource::{detect_format_from_path, load_boards_by_format};
+use sudoku_solver::solvers::{
     backtracking::BacktrackingSolver,
+    logical::rate,
     solver::{Solver, SolverKind, solve_with_strategy},
 };
-use sudoku::Sudoku;
-use validator::Validator;
+use sudoku_solver::sudoku::Sudoku;
+use sudoku_solver::validator::Validator;
 
 pub fn solve_sudoku_boards_from_file(file_path: &str) -> Result<Vec<Sudoku>> {
     let format = detect_format_from_path(file_path)
@@ -17,24 +17,25 @@ pub fn solve_sudoku_boards_from_file(file_path: &str) -> Result<Vec<Sudoku>> {
 
     let sudoku_boards = load_boards_by_format(format, file_path)?;
 
+    // Repeated puzzles, including rotated or relabelled copies, are solved once.
+    let solutions = batch::solve_all(&sudoku_boards, Dedup::Symmetry, |sudoku| {
+        if !Validator::is_valid_board(sudoku) {
+            return Err(anyhow::anyhow!("is invalid"));
+        }
+        BacktrackingSolver::solve(sudoku).map_err(|_| anyhow::anyhow!("is valid but unsolvable"))
+    });
+
     let mut valid_boards = Vec::new();
 
-    for (i, mut sudoku) in sudoku_boards.into_iter().enumerate() {
-        if Validator::is_valid_board(&sudoku) {
-            match BacktrackingSolver::solve(&mut sudoku) {
-                Ok(_) => {
-                    valid_boards.push(sudoku);
-                    println!("Sudoku #{} solved successfully.", i + 1);
-                }
-                Err(_) => {
-                    eprintln!(
-                        "Error: Sudoku #{} is valid but unsolvable, skipping.",
-                        i + 1
-                    );
-                }
+    for (i, solution) in solutions.into_iter().enumerate() {
+        match solution {
+            Ok(sudoku) => {
+                valid_boards.push(sudoku);
+                println!("Sudoku #{} solved successfully.", i + 1);
+            }
+            Err(e) => {
+                eprintln!("Error: Sudoku #{} {}, skipping.", i + 1, e);
             }
-        } else {
-            eprintln!("Error: Sudoku #{} is invalid, skipping.", i + 1);
         }
     }
 
@@ -72,55 +73,113 @@ fn solve_with_strategy_test(kind: SolverKind, input: &str, expected_output: &str
 }
 
 fn solve_sudoku_from_different_paths() -> Result<()> {
-    let file_paths = vec!["inputs/first.json", "inputs/first.csv"];
-
-    file_paths
-        .iter()
-        .map(|path| {
-            println!("==> Processing file: {}", path);
-
-            let format = detect_format_from_path(path)
-                .ok_or_else(|| anyhow::anyhow!("Could not detect format from path: {}", path))?;
-
-            let boards = load_boards_by_format(format, path)?;
-
-            let _ = boards
-                .into_iter()
-                .enumerate()
-                .filter_map(|(i, mut board)| {
-                    if Validator::is_valid_board(&board) {
-                        match BacktrackingSolver::solve(&mut board) {
-                            Ok(_) => {
-                                println!("Sudoku #{} solved successfully.\n{}", i + 1, board);
-                                Some(board)
-                            }
-                            Err(e) => {
-                                eprintln!("Sudoku #{} could not be solved: {:?}", i + 1, e);
-                                None
-                            }
+    let file_paths = ["inputs/first.json", "inputs/first.csv"];
+
+    file_paths.iter().try_for_each(|path| {
+        println!("==> Processing file: {}", path);
+
+        let format = detect_format_from_path(path)
+            .ok_or_else(|| anyhow::anyhow!("Could not detect format from path: {}", path))?;
+
+        let boards = load_boards_by_format(format, path)?;
+
+        let _ = boards
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, mut board)| {
+                if Validator::is_valid_board(&board) {
+                    match BacktrackingSolver::solve(&mut board) {
+                        Ok(_) => {
+                            println!("Sudoku #{} solved successfully.\n{:#}", i + 1, board);
+                            Some(board)
+                        }
+                        Err(e) => {
+                            eprintln!("Sudoku #{} could not be solved: {:?}", i + 1, e);
+                            None
                         }
-                    } else {
-                        eprintln!("Sudoku #{} is invalid, skipping.", i + 1);
-                        None
                     }
-                })
-                .collect::<Vec<_>>();
+                } else {
+                    eprintln!("Sudoku #{} is invalid, skipping.", i + 1);
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
 
-            println!();
-            Ok(())
-        })
-        .collect::<Result<()>>()?;
+        println!();
+        Ok(())
+    })
+}
 
+// A file in any supported format, or a single puzzle string.
+fn load_sudoku_boards(target: &str) -> Result<Vec<Sudoku>> {
+    match detect_format_from_path(target) {
+        Some(format) => load_boards_by_format(format, target),
+        None => Ok(vec![Sudoku::from_string(target)?]),
+    }
+}
+
+fn rate_sudoku_boards(target: &str) -> Result<()> {
+    let boards = load_sudoku_boards(target)?;
+
+    for (i, board) in boards.iter().enumerate() {
+        match rate(board) {
+            Ok(rating) => println!("Sudoku #{}: {}", i + 1, rating),
+            Err(e) => eprintln!("Sudoku #{} could not be rated: {}", i + 1, e),
+        }
+    }
     Ok(())
 }
 
+// Plays board `number`, counted from 1, of the target.
+fn play_sudoku(target: &str, number: usize) -> Result<()> {
+    play(pick_sudoku(target, number)?)
+}
+
+fn pick_sudoku(target: &str, number: usize) -> Result<Sudoku> {
+    if number == 0 {
+        return Err(anyhow::anyhow!("Sudokus are numbered from 1"));
+    }
+    load_sudoku_boards(target)?
+        .into_iter()
+        .nth(number - 1)
+        .ok_or_else(|| anyhow::anyhow!("There is no Sudoku #{} in {}", number, target))
+}
+
+#[cfg(feature = "tui")]
+fn play(board: Sudoku) -> Result<()> {
+    sudoku_solver::tui::play(board)
+}
+
+#[cfg(not(feature = "tui"))]
+fn play(_board: Sudoku) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "Play mode needs the tui feature: cargo run --features tui -- play <puzzle|file>"
+    ))
+}
+
 fn main() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.as_slice() {
+        [] => {}
+        [command, target] if command == "rate" => return rate_sudoku_boards(target),
+        [command, target] if command == "play" => return play_sudoku(target, 1),
+        [command, target, number] if command == "play" => {
+            return play_sudoku(target, number.parse()?);
+        }
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Usage: sudoku_solver [rate <puzzle|file> | play <puzzle|file> [number]]"
+            ));
+        }
+    }
+
     let input = "050000024904005000876240000010002080300000750409017200000900000247000000000600032";
     let expected_output =
         "153786924924135678876249315715362489362498751489517263638921547247853196591674832";
     solve_sudoku_from_string::<BacktrackingSolver>(input, expected_output);
     solve_with_strategy_test(SolverKind::Backtracking, input, expected_output);
     solve_with_strategy_test(SolverKind::Heuristic, input, expected_output);
+    solve_with_strategy_test(SolverKind::Logical, input, expected_output);
 
     solve_sudoku_from_different_paths()?;
 
@@ -163,6 +222,15 @@ mod tests {
         solve_with_strategy_test(SolverKind::Heuristic, input, expected_output);
     }
 
+    #[test]
+    fn test_strategy_easy_sudoku_from_string_logical_solution() {
+        let input =
+            "050000024904005000876240000010002080300000750409017200000900000247000000000600032";
+        let expected_output =
+            "153786924924135678876249315715362489362498751489517263638921547247853196591674832";
+        solve_with_strategy_test(SolverKind::Logical, input, expected_output);
+    }
+
     #[test]
     fn test_easy_sudoku_from_string_backtracking_solution() {
         let input =
@@ -217,6 +285,55 @@ mod tests {
         println!("Solved Sudoku: \n{}", solved_boards[0]);
     }
 
+    #[test]
+    fn test_solve_four_by_four_board_from_csv() {
+        let path = "inputs/small.csv";
+
+        let solved_boards = solve_sudoku_boards_from_file(path).unwrap();
+        assert_eq!(solved_boards.len(), 1, "Expected 1 solved board");
+        assert_eq!(solved_boards[0].size(), 4);
+        assert!(!solved_boards[0].to_string().contains('0'));
+    }
+
+    #[test]
+    fn test_solve_jigsaw_board_from_csv() {
+        let solved_boards = solve_sudoku_boards_from_file("inputs/jigsaw.csv").unwrap();
+
+        assert!(
+            solved_boards[0].regions().is_some(),
+            "Region map should load"
+        );
+        assert_eq!(solved_boards[0].to_string(), "3124241313424231");
+    }
+
+    #[test]
+    fn test_solve_killer_board_from_json() {
+        let solved_boards = solve_sudoku_boards_from_file("inputs/killer.json").unwrap();
+
+        assert_eq!(
+            solved_boards[0].constraints().len(),
+            17,
+            "Cages should load"
+        );
+        assert_eq!(
+            solved_boards[0].to_string(),
+            "326145451263164532532416243651615324"
+        );
+    }
+
+    #[test]
+    fn test_pick_sudoku_counts_from_one() {
+        let path = "inputs/multiple_boards.json";
+        let boards = load_sudoku_boards(path).unwrap();
+
+        assert_eq!(
+            pick_sudoku(path, 2).unwrap().to_string(),
+            boards[1].to_string()
+        );
+        assert!(pick_sudoku(path, 0).is_err(), "There is no Sudoku #0");
+        assert!(pick_sudoku(path, boards.len() + 1).is_err());
+    }
+
     #[test]
     fn test_solve_multiple_boards_from_json() {
         let path = "inputs/multiple_boards.json";
@@ -299,4 +416,29 @@ mod tests {
         let result = solve_sudoku_boards_from_file(&file_path);
         assert!(result.is_err(), "Expected error due to no Sudoku boards");
     }
+
+    #[test]
+    fn test_rate_sudoku_boards_from_file_and_string() {
+        assert!(rate_sudoku_boards("inputs/multiple_boards.json").is_ok());
+        assert!(
+            rate_sudoku_boards(
+                "530070000600195000098000060800060003400803001700020006060000280000419005000080079"
+            )
+            .is_ok()
+        );
+        assert!(rate_sudoku_boards("not a sudoku").is_err());
+    }
+
+    #[test]
+    fn test_load_pencil_mark_file() {
+        let boards = load_sudoku_boards("inputs/pencilmarks.txt").unwrap();
+
+        assert_eq!(boards.len(), 1);
+        assert_eq!(
+            boards[0].to_string(),
+            "530070000600195000098000060800060003400853001700020006060007284000419035000080079",
+            "Cells with a single candidate are the clues"
+        );
+        assert!(rate_sudoku_boards("inputs/pencilmarks.txt").is_ok());
+    }
 }
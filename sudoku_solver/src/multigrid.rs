@@ -0,0 +1,354 @@
+use std::{collections::BTreeMap, fmt};
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::{shape::Shape, sudoku::Sudoku, validator::Validator};
+
+// Samurai and other gattai puzzles: sub-grids placed on a shared canvas.
+// Where grids overlap, the cells are shared and must hold the same digit in
+// every grid covering them.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(try_from = "MultiGridData")]
+pub struct MultiGrid {
+    grids: Vec<Placed>,
+}
+
+// A sub-grid and the canvas position of its top-left cell. In JSON the
+// position sits next to the usual board fields.
+#[derive(Debug, Serialize, Deserialize)]
+struct Placed {
+    row: usize,
+    col: usize,
+    #[serde(flatten)]
+    sudoku: Sudoku,
+}
+
+// A canvas cell as (grid, row, col) in each grid that covers it.
+type Linked = Vec<(usize, usize, usize)>;
+
+#[derive(Deserialize)]
+struct MultiGridData {
+    grids: Vec<Placed>,
+}
+
+impl TryFrom<MultiGridData> for MultiGrid {
+    type Error = anyhow::Error;
+
+    fn try_from(data: MultiGridData) -> Result<Self> {
+        Self::new(
+            data.grids
+                .into_iter()
+                .map(|placed| (placed.row, placed.col, placed.sudoku))
+                .collect(),
+        )
+    }
+}
+
+impl MultiGrid {
+    // Top-left corners of the five 9x9 grids of a Samurai, on a 21x21 canvas.
+    pub const SAMURAI: [(usize, usize); 5] = [(0, 0), (0, 12), (6, 6), (12, 0), (12, 12)];
+
+    // Grids as (row, col, sudoku), positioned by their top-left cell.
+    // Overlapping cells must agree.
+    pub fn new(grids: Vec<(usize, usize, Sudoku)>) -> Result<Self> {
+        if grids.is_empty() {
+            return Err(anyhow!("A multi-grid needs at least one grid"));
+        }
+
+        let grids: Vec<Placed> = grids
+            .into_iter()
+            .map(|(row, col, sudoku)| Placed { row, col, sudoku })
+            .collect();
+        let multigrid = Self { grids };
+        for ((row, col), links) in multigrid.links() {
+            let (first, first_row, first_col) = links[0];
+            let value = multigrid.grids[first].sudoku[first_row][first_col];
+            if let Some(&(other, ..)) = links
+                .iter()
+                .find(|&&(grid, r, c)| multigrid.grids[grid].sudoku[r][c] != value)
+            {
+                return Err(anyhow!(
+                    "Grids {} and {} disagree at r{}c{}",
+                    first + 1,
+                    other + 1,
+                    row + 1,
+                    col + 1
+                ));
+            }
+        }
+        Ok(multigrid)
+    }
+
+    // One line per canvas row: a digit, `0` or `.` for each cell and a space
+    // where no grid reaches. Every grid is a classic 9x9 with its top-left
+    // cell at one of `corners`.
+    pub fn from_canvas(text: &str, corners: &[(usize, usize)]) -> Result<Self> {
+        let lines: Vec<Vec<char>> = text.lines().map(|line| line.chars().collect()).collect();
+        let size = Shape::CLASSIC.size();
+
+        let grids = corners
+            .iter()
+            .map(|&(top, left)| {
+                let board = (top..top + size)
+                    .map(|row| {
+                        (left..left + size)
+                            .map(|col| {
+                                lines
+                                    .get(row)
+                                    .and_then(|line| line.get(col))
+                                    .and_then(|&c| Shape::parse_symbol(c))
+                                    .ok_or_else(|| {
+                                        anyhow!("Missing cell at r{}c{}", row + 1, col + 1)
+                                    })
+                            })
+                            .collect::<Result<Vec<u8>>>()
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok((top, left, Sudoku::new(board)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Self::new(grids)
+    }
+
+    pub fn samurai(text: &str) -> Result<Self> {
+        Self::from_canvas(text, &Self::SAMURAI)
+    }
+
+    // Sub-grids with the canvas position of their top-left cell.
+    pub fn grids(&self) -> impl Iterator<Item = (usize, usize, &Sudoku)> {
+        self.grids
+            .iter()
+            .map(|placed| (placed.row, placed.col, &placed.sudoku))
+    }
+
+    // The digit at a canvas position, 0 when empty, or `None` off every grid.
+    pub fn get(&self, row: usize, col: usize) -> Option<u8> {
+        self.grids
+            .iter()
+            .find(|placed| placed.covers(row, col))
+            .map(|placed| placed.sudoku[row - placed.row][col - placed.col])
+    }
+
+    // Overlaps agree by construction, so each grid is checked on its own.
+    pub fn is_valid(&self) -> bool {
+        self.grids
+            .iter()
+            .all(|placed| Validator::is_valid_board(&placed.sudoku))
+    }
+
+    // Backtracking over the whole canvas: a digit is tried in a shared cell
+    // only if every grid covering it allows it.
+    pub fn solve(&mut self) -> Result<()> {
+        if !self.is_valid() {
+            return Err(anyhow!("Invalid board"));
+        }
+        let links: Vec<Linked> = self.links().into_values().collect();
+        if !self.solve_cells(&links) {
+            return Err(anyhow!("Unsolvable board"));
+        }
+        Ok(())
+    }
+
+    fn solve_cells(&mut self, links: &[Linked]) -> bool {
+        let mut best: Option<(&Linked, Vec<u8>)> = None;
+        for cell in links.iter().filter(|cell| self.value(cell) == 0) {
+            let size = self.grids[cell[0].0].sudoku.size();
+            let choices: Vec<u8> = (1..=size as u8)
+                .filter(|&value| {
+                    cell.iter().all(|&(grid, row, col)| {
+                        Validator::is_valid(&self.grids[grid].sudoku, row, col, value)
+                    })
+                })
+                .collect();
+            if best
+                .as_ref()
+                .is_none_or(|(_, best)| choices.len() < best.len())
+            {
+                let done = choices.len() <= 1;
+                best = Some((cell, choices));
+                if done {
+                    break;
+                }
+            }
+        }
+
+        let Some((cell, choices)) = best else {
+            return true;
+        };
+        for value in choices {
+            self.fill(cell, value);
+            if self.solve_cells(links) {
+                return true;
+            }
+        }
+        self.fill(cell, 0);
+        false
+    }
+
+    fn value(&self, cell: &[(usize, usize, usize)]) -> u8 {
+        let (grid, row, col) = cell[0];
+        self.grids[grid].sudoku[row][col]
+    }
+
+    fn fill(&mut self, cell: &[(usize, usize, usize)], value: u8) {
+        for &(grid, row, col) in cell {
+            self.grids[grid].sudoku.fill_cell(row, col, value);
+        }
+    }
+
+    // Every canvas position with the grid cells linked there.
+    fn links(&self) -> BTreeMap<(usize, usize), Linked> {
+        let mut links: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for (grid, placed) in self.grids.iter().enumerate() {
+            let size = placed.sudoku.size();
+            for row in 0..size {
+                for col in 0..size {
+                    links
+                        .entry((placed.row + row, placed.col + col))
+                        .or_default()
+                        .push((grid, row, col));
+                }
+            }
+        }
+        links
+    }
+}
+
+impl Placed {
+    fn covers(&self, row: usize, col: usize) -> bool {
+        let size = self.sudoku.size();
+        (self.row..self.row + size).contains(&row) && (self.col..self.col + size).contains(&col)
+    }
+}
+
+// The canvas, in the format `from_canvas` reads, with `.` for empty cells.
+impl fmt::Display for MultiGrid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (rows, cols) = self.grids.iter().fold((0, 0), |(rows, cols), placed| {
+            let size = placed.sudoku.size();
+            (rows.max(placed.row + size), cols.max(placed.col + size))
+        });
+        for row in 0..rows {
+            let line: String = (0..cols)
+                .map(|col| match self.get(row, col) {
+                    Some(0) => '.',
+                    Some(value) => Shape::symbol(value),
+                    None => ' ',
+                })
+                .collect();
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMURAI: &str = "\
+.3.2.1...   1..3.....
+..8...9.2   ...916...
+17...936.   69....1.2
+.........   36.4...19
+2..7...56   8..2..57.
+7.....4..   .....1...
+3.612..7...6..18.49.6
+82.9...3.8.25........
+..76.3...73.9...2..4.
+      .82....1.
+      .6......9
+      .1..2.76.
+...716.5.....9.3....1
+.31..8..62...5....9..
+.....2..83...7..1....
+1.2....7.   9....245.
+..91...3.   537.84..9
+.....76..   ....6..3.
+.17...3..   ..5...8..
+.953....2   .6.54..9.
+8.3.5....   ....2.765
+";
+
+    const SOLUTION: &str = "\
+639251748   158342697
+458367912   723916485
+172849365   694758132
+583416297   365487219
+294738156   819263574
+761592483   472591368
+346125879456231874956
+825974631892547639821
+917683524731986125743
+      982674315
+      467513829
+      315928764
+984716253167498356271
+231598746289153278946
+576432198345672419583
+142863579   916732458
+769125834   537184629
+358947621   284965137
+417289365   725693814
+695371482   861547392
+823654917   349821765
+";
+
+    #[test]
+    fn test_samurai_links_shared_boxes() {
+        let samurai = MultiGrid::samurai(SAMURAI).unwrap();
+
+        assert_eq!(samurai.grids().count(), 5);
+        assert_eq!(samurai.get(6, 7), Some(7), "Shared by grids 1 and 3");
+        assert_eq!(samurai.get(0, 10), None, "Between the top grids");
+        assert_eq!(samurai.to_string(), SAMURAI);
+    }
+
+    #[test]
+    fn test_solve_samurai() {
+        let mut samurai = MultiGrid::samurai(SAMURAI).unwrap();
+
+        assert!(samurai.solve().is_ok());
+        assert_eq!(samurai.to_string(), SOLUTION);
+        assert!(samurai.is_valid());
+    }
+
+    #[test]
+    fn test_new_rejects_disagreeing_overlap() {
+        let empty = || Sudoku::from_string(&"0".repeat(81)).unwrap();
+        let mut corner = vec![vec![0; 9]; 9];
+        corner[8][8] = 5;
+
+        assert!(MultiGrid::new(vec![(0, 0, empty()), (6, 6, empty())]).is_ok());
+        assert!(
+            MultiGrid::new(vec![(0, 0, Sudoku::new(corner).unwrap()), (6, 6, empty())]).is_err(),
+            "r9c9 is 5 in grid 1 but empty in grid 2"
+        );
+        assert!(MultiGrid::new(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_solve_keeps_overlaps_linked() {
+        let empty = || Sudoku::from_string(&"0".repeat(81)).unwrap();
+        let mut pair = MultiGrid::new(vec![(0, 0, empty()), (6, 6, empty())]).unwrap();
+
+        assert!(pair.solve().is_ok());
+        assert!(pair.is_valid());
+        let grids: Vec<_> = pair.grids().collect();
+        assert_eq!(
+            grids[0].2[8][8], grids[1].2[2][2],
+            "r9c9 is shared by both grids"
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let samurai = MultiGrid::samurai(SAMURAI).unwrap();
+        let json = serde_json::to_string(&samurai).unwrap();
+
+        assert!(json.starts_with(r#"{"grids":[{"row":0,"col":0,"board":"#));
+        let parsed: MultiGrid = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.to_string(), SAMURAI);
+    }
+}
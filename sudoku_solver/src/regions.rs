@@ -0,0 +1,145 @@
+use anyhow::{Result, anyhow};
+
+use crate::shape::Shape;
+
+// Irregular boxes for jigsaw puzzles. Every cell names its region, numbered
+// from 0; regions take the place of the rectangular boxes of the shape.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Regions {
+    size: usize,
+    ids: Vec<u8>,
+    cells: Vec<Vec<(usize, usize)>>,
+}
+
+impl Regions {
+    // Each region must have `size` orthogonally connected cells.
+    pub fn new(rows: &[Vec<u8>]) -> Result<Self> {
+        let size = rows.len();
+        if !(2..=Shape::MAX_SIZE).contains(&size) || rows.iter().any(|row| row.len() != size) {
+            return Err(anyhow!(
+                "Region map must be square, at most {0}x{0}",
+                Shape::MAX_SIZE
+            ));
+        }
+
+        let mut cells = vec![Vec::new(); size];
+        for (row, ids) in rows.iter().enumerate() {
+            for (col, &id) in ids.iter().enumerate() {
+                let region = cells.get_mut(id as usize).ok_or_else(|| {
+                    anyhow!("Region {} at r{}c{} is out of range", id, row + 1, col + 1)
+                })?;
+                region.push((row, col));
+            }
+        }
+        for (id, region) in cells.iter().enumerate() {
+            if region.len() != size {
+                return Err(anyhow!(
+                    "Region {} has {} cells instead of {}",
+                    id,
+                    region.len(),
+                    size
+                ));
+            }
+            if !is_connected(region) {
+                return Err(anyhow!("Region {} is not connected", id));
+            }
+        }
+
+        Ok(Self {
+            size,
+            ids: rows.concat(),
+            cells,
+        })
+    }
+
+    // The rectangular boxes of a shape as a region map.
+    pub fn from_shape(shape: Shape) -> Self {
+        let size = shape.size();
+        let rows: Vec<Vec<u8>> = (0..size)
+            .map(|row| (0..size).map(|col| shape.box_of(row, col) as u8).collect())
+            .collect();
+        Self::new(&rows).expect("Boxes form valid regions")
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn region_of(&self, row: usize, col: usize) -> usize {
+        self.ids[row * self.size + col] as usize
+    }
+
+    // Cells of a region, row by row.
+    pub fn cells(&self, region: usize) -> &[(usize, usize)] {
+        &self.cells[region]
+    }
+
+    pub fn rows(&self) -> Vec<Vec<u8>> {
+        self.ids.chunks(self.size).map(<[u8]>::to_vec).collect()
+    }
+}
+
+fn is_connected(region: &[(usize, usize)]) -> bool {
+    let mut reached = vec![region[0]];
+    let mut i = 0;
+    while i < reached.len() {
+        let (row, col) = reached[i];
+        for &other in region {
+            if !reached.contains(&other) && row.abs_diff(other.0) + col.abs_diff(other.1) == 1 {
+                reached.push(other);
+            }
+        }
+        i += 1;
+    }
+    reached.len() == region.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jigsaw_rows() -> Vec<Vec<u8>> {
+        vec![
+            vec![0, 0, 0, 1],
+            vec![0, 2, 1, 1],
+            vec![2, 2, 3, 1],
+            vec![2, 3, 3, 3],
+        ]
+    }
+
+    #[test]
+    fn test_new() {
+        let regions = Regions::new(&jigsaw_rows()).unwrap();
+
+        assert_eq!(regions.region_of(1, 1), 2);
+        assert_eq!(regions.cells(1), [(0, 3), (1, 2), (1, 3), (2, 3)]);
+        assert_eq!(regions.rows(), jigsaw_rows());
+    }
+
+    #[test]
+    fn test_new_rejects_malformed_regions() {
+        let mut uneven = jigsaw_rows();
+        uneven[0][0] = 1;
+        assert!(
+            Regions::new(&uneven).is_err(),
+            "Regions must be equal in size"
+        );
+
+        let mut split = jigsaw_rows();
+        split[0][2] = 2;
+        split[1][1] = 0;
+        assert!(Regions::new(&split).is_err(), "Region 2 is split in two");
+
+        let mut out_of_range = jigsaw_rows();
+        out_of_range[3][3] = 4;
+        assert!(Regions::new(&out_of_range).is_err());
+    }
+
+    #[test]
+    fn test_from_shape() {
+        let regions = Regions::from_shape(Shape::new(2, 3).unwrap());
+
+        assert_eq!(regions.region_of(1, 3), 1);
+        assert_eq!(regions.cells(2)[0], (2, 0));
+    }
+}
@@ -0,0 +1,460 @@
+use std::{
+    fmt,
+    io::Read,
+    num::NonZero,
+    sync::{
+        Arc, Mutex,
+        mpsc::{self, TrySendError},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Result, anyhow};
+use serde::de::DeserializeOwned;
+use serde_json::{Value, error::Category, json};
+use tiny_http::{Header, Request, Response, Server};
+
+use crate::{
+    batch::{self, Dedup},
+    solvers::backtracking::BacktrackingSolver,
+    sudoku::Sudoku,
+    validator::Validator,
+};
+
+// Bounds on the work the server takes on, per request and at once.
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub max_body_bytes: usize,
+    pub max_batch: usize,
+    pub timeout: Duration,
+    // Requests answered at once. As many again can wait their turn; any more
+    // are turned away.
+    pub workers: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: 64 * 1024,
+            max_batch: 100,
+            timeout: Duration::from_secs(5),
+            workers: thread::available_parallelism().map_or(1, NonZero::get),
+        }
+    }
+}
+
+// A status code and its JSON body. Errors have the body
+// `{"error": {"code": ..., "message": ...}}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reply {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Reply {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, code: &str, message: impl fmt::Display) -> Self {
+        Self {
+            status,
+            body: json!({ "error": { "code": code, "message": message.to_string() } }),
+        }
+    }
+}
+
+// Listens on the loopback interface only and answers requests on a fixed
+// pool of worker threads, until the process is stopped.
+pub fn serve(port: u16, limits: Limits) -> Result<()> {
+    let server = Server::http(("127.0.0.1", port))
+        .map_err(|err| anyhow!("Failed to start the server: {}", err))?;
+    run(&server, limits);
+    Ok(())
+}
+
+fn run(server: &Server, limits: Limits) {
+    let workers = limits.workers.max(1);
+    let (sender, receiver) = mpsc::sync_channel::<Request>(workers);
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..workers {
+        let receiver = Arc::clone(&receiver);
+        thread::spawn(move || {
+            loop {
+                let request = match receiver.lock() {
+                    Ok(receiver) => receiver.recv(),
+                    Err(_) => return,
+                };
+                let Ok(request) = request else { return };
+                if let Err(err) = respond(request, limits) {
+                    eprintln!("Failed to answer a request: {}", err);
+                }
+            }
+        });
+    }
+
+    for request in server.incoming_requests() {
+        if let Err(TrySendError::Full(request)) = sender.try_send(request) {
+            let busy = Reply::error(503, "busy", "Too many requests, try again later");
+            if let Err(err) = send(request, busy) {
+                eprintln!("Failed to answer a request: {}", err);
+            }
+        }
+    }
+}
+
+fn respond(mut request: Request, limits: Limits) -> Result<()> {
+    let reply = match read_body(&mut request, limits.max_body_bytes) {
+        Ok(body) => handle(request.method().as_str(), request.url(), body, limits),
+        Err(reply) => reply,
+    };
+    send(request, reply)
+}
+
+fn send(request: Request, reply: Reply) -> Result<()> {
+    let content_type = Header::from_bytes("Content-Type", "application/json")
+        .map_err(|_| anyhow!("Invalid header"))?;
+    let response = Response::from_string(reply.body.to_string())
+        .with_status_code(reply.status)
+        .with_header(content_type);
+    request.respond(response)?;
+    Ok(())
+}
+
+// Reads at most one byte past the limit, so oversized bodies are never held
+// in full.
+fn read_body(request: &mut Request, limit: usize) -> Result<Vec<u8>, Reply> {
+    let too_large = || {
+        Reply::error(
+            413,
+            "too_large",
+            format!("Bodies are limited to {} bytes", limit),
+        )
+    };
+    if request.body_length().is_some_and(|length| length > limit) {
+        return Err(too_large());
+    }
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(limit as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(|err| Reply::error(400, "unreadable_body", err))?;
+    if body.len() > limit {
+        return Err(too_large());
+    }
+    Ok(body)
+}
+
+// Routes a request, independent of the transport. `POST /solve` takes a
+// board and `POST /batch` a list of boards, both in the JSON shape the input
+// files use; `POST /validate` checks a board without solving it.
+pub fn handle(method: &str, url: &str, body: Vec<u8>, limits: Limits) -> Reply {
+    let path = url.split('?').next().unwrap_or(url);
+    let deadline = Instant::now() + limits.timeout;
+    match (method, path) {
+        ("POST", "/solve") => solve(&body, deadline),
+        ("POST", "/validate") => validate(&body),
+        ("POST", "/batch") => solve_batch(&body, limits.max_batch, deadline),
+        (_, "/solve" | "/validate" | "/batch") => Reply::error(
+            405,
+            "method_not_allowed",
+            format!("{} only accepts POST", path),
+        ),
+        _ => Reply::error(404, "not_found", format!("No endpoint at {}", path)),
+    }
+}
+
+// The solver stops searching at the deadline, so a failure after it says
+// nothing about the board.
+fn timed_out(deadline: Instant) -> Option<Reply> {
+    (Instant::now() >= deadline)
+        .then(|| Reply::error(503, "timeout", "Ran out of time before solving"))
+}
+
+// Malformed JSON is the client's mistake; well-formed JSON that is not a
+// board, or a board breaking its own rules, is rejected as unprocessable.
+fn parse<T: DeserializeOwned>(body: &[u8]) -> Result<T, Reply> {
+    serde_json::from_slice(body).map_err(|err| match err.classify() {
+        Category::Data => Reply::error(422, "invalid_board", err),
+        _ => Reply::error(400, "invalid_json", err),
+    })
+}
+
+fn solve(body: &[u8], deadline: Instant) -> Reply {
+    let sudoku: Sudoku = match parse(body) {
+        Ok(sudoku) => sudoku,
+        Err(reply) => return reply,
+    };
+    let mut solution = sudoku.clone();
+    match solve_board(&mut solution, deadline) {
+        Ok(()) => Reply::ok(json!({ "solution": solution })),
+        Err(err) => timed_out(deadline).unwrap_or_else(|| solve_error(&sudoku, err)),
+    }
+}
+
+fn solve_board(sudoku: &mut Sudoku, deadline: Instant) -> Result<()> {
+    if !Validator::is_valid_board(sudoku) {
+        return Err(anyhow!("Sudoku is invalid"));
+    }
+    BacktrackingSolver::solve_before(sudoku, deadline)
+}
+
+// Tells a board that breaks its rules apart from one with no solution.
+fn solve_error(board: &Sudoku, err: impl fmt::Display) -> Reply {
+    if Validator::is_valid_board(board) {
+        Reply::error(422, "unsolvable", err)
+    } else {
+        Reply::error(422, "invalid_board", err)
+    }
+}
+
+fn validate(body: &[u8]) -> Reply {
+    match parse::<Sudoku>(body) {
+        Ok(sudoku) => {
+            let valid = Validator::is_valid_board(&sudoku);
+            let solved = valid && sudoku.rows().flatten().all(|&value| value != 0);
+            Reply::ok(json!({ "valid": valid, "solved": solved }))
+        }
+        Err(reply) if reply.status == 422 => Reply::ok(json!({
+            "valid": false,
+            "solved": false,
+            "reason": reply.body["error"]["message"],
+        })),
+        Err(reply) => reply,
+    }
+}
+
+// Each board gets `{"solution": ...}` or `{"error": ...}`, in request order.
+// Repeated boards, including rotated or relabelled copies, are solved once.
+fn solve_batch(body: &[u8], max_batch: usize, deadline: Instant) -> Reply {
+    let boards: Vec<Sudoku> = match parse(body) {
+        Ok(boards) => boards,
+        Err(reply) => return reply,
+    };
+    if boards.len() > max_batch {
+        return Reply::error(
+            413,
+            "too_large",
+            format!("Batches are limited to {} boards", max_batch),
+        );
+    }
+
+    let solutions = batch::solve_all_before(&boards, Dedup::Symmetry, deadline, |board| {
+        solve_board(board, deadline)
+    });
+    if solutions.iter().any(Result::is_err)
+        && let Some(reply) = timed_out(deadline)
+    {
+        return reply;
+    }
+    let results: Vec<Value> = boards
+        .iter()
+        .zip(solutions)
+        .map(|(board, solution)| match solution {
+            Ok(solution) => json!({ "solution": solution }),
+            Err(err) => solve_error(board, err).body,
+        })
+        .collect();
+    Reply::ok(json!({ "results": results }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpStream,
+    };
+
+    use super::*;
+
+    const PUZZLE: &str = r#"{"board": [[5,3,0,0,7,0,0,0,0],[6,0,0,1,9,5,0,0,0],[0,9,8,0,0,0,0,6,0],[8,0,0,0,6,0,0,0,3],[4,0,0,8,0,3,0,0,1],[7,0,0,0,2,0,0,0,6],[0,6,0,0,0,0,2,8,0],[0,0,0,4,1,9,0,0,5],[0,0,0,0,8,0,0,7,9]]}"#;
+    // Built against naive backtracking, which needs minutes on it.
+    const HARD: &str = r#"{"board": [[0,0,0,0,0,0,0,0,0],[0,0,0,0,0,3,0,8,5],[0,0,1,0,2,0,0,0,0],[0,0,0,5,0,7,0,0,0],[0,0,4,0,0,0,1,0,0],[0,9,0,0,0,0,0,0,0],[5,0,0,0,0,0,0,7,3],[0,0,2,0,1,0,0,0,0],[0,0,0,0,4,0,0,0,9]]}"#;
+    const STUCK: &str = r#"{"board": [[1,2,0,0],[0,0,4,0],[0,0,0,3],[0,0,0,0]]}"#;
+
+    fn post(url: &str, body: &str) -> Reply {
+        handle("POST", url, body.as_bytes().to_vec(), Limits::default())
+    }
+
+    fn code(reply: &Reply) -> &str {
+        reply.body["error"]["code"].as_str().unwrap_or_default()
+    }
+
+    #[test]
+    fn test_solve() {
+        let reply = post("/solve", PUZZLE);
+        assert_eq!(reply.status, 200);
+        assert_eq!(reply.body["solution"]["board"][0][2], 4);
+
+        let reply = post("/solve", STUCK);
+        assert_eq!((reply.status, code(&reply)), (422, "unsolvable"));
+        let reply = post("/solve", "{\"board\": [[1,1],[0,0]]");
+        assert_eq!((reply.status, code(&reply)), (400, "invalid_json"));
+        let reply = post(
+            "/solve",
+            r#"{"board": [[1,1,0,0],[0,0,0,0],[0,0,0,0],[0,0,0,0]]}"#,
+        );
+        assert_eq!(
+            (reply.status, code(&reply)),
+            (422, "invalid_board"),
+            "Two 1s in the first row"
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let reply = post("/validate", PUZZLE);
+        assert_eq!(reply.body, json!({ "valid": true, "solved": false }));
+
+        let reply = post(
+            "/validate",
+            r#"{"board": [[1,1,0,0],[0,0,0,0],[0,0,0,0],[0,0,0,0]]}"#,
+        );
+        assert_eq!(reply.body, json!({ "valid": false, "solved": false }));
+
+        let reply = post("/validate", r#"{"board": [[1,2,3]]}"#);
+        assert_eq!(reply.status, 200);
+        assert_eq!(reply.body["valid"], false);
+        assert!(
+            reply.body["reason"].is_string(),
+            "Boards that cannot be read say why"
+        );
+    }
+
+    #[test]
+    fn test_batch() {
+        let body = format!("[{}, {}, {}]", PUZZLE, STUCK, PUZZLE);
+        let reply = post("/batch", &body);
+
+        assert_eq!(reply.status, 200);
+        let results = reply.body["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], results[2]);
+        assert_eq!(results[1]["error"]["code"], "unsolvable");
+
+        let limits = Limits {
+            max_batch: 2,
+            ..Limits::default()
+        };
+        let reply = handle("POST", "/batch", body.into_bytes(), limits);
+        assert_eq!((reply.status, code(&reply)), (413, "too_large"));
+    }
+
+    #[test]
+    fn test_routing() {
+        let reply = handle("GET", "/solve", Vec::new(), Limits::default());
+        assert_eq!((reply.status, code(&reply)), (405, "method_not_allowed"));
+        let reply = post("/unknown", "");
+        assert_eq!((reply.status, code(&reply)), (404, "not_found"));
+        assert_eq!(post("/solve?verbose=1", PUZZLE).status, 200);
+    }
+
+    #[test]
+    fn test_timeout_stops_the_solver() {
+        let limits = Limits {
+            timeout: Duration::from_millis(50),
+            ..Limits::default()
+        };
+        let started = Instant::now();
+
+        let reply = handle("POST", "/solve", HARD.as_bytes().to_vec(), limits);
+        assert_eq!((reply.status, code(&reply)), (503, "timeout"));
+        let body = format!("[{}, {}]", PUZZLE, HARD);
+        let reply = handle("POST", "/batch", body.into_bytes(), limits);
+        assert_eq!((reply.status, code(&reply)), (503, "timeout"));
+        assert!(
+            started.elapsed() < Duration::from_secs(2),
+            "Nothing keeps running past the deadline"
+        );
+    }
+
+    #[test]
+    fn test_timeout_covers_matching_symmetric_boards() {
+        // Each empty board takes over a second to canonicalise.
+        let empty = r#"{"board": [[0,0,0,0,0,0,0,0,0],[0,0,0,0,0,0,0,0,0],[0,0,0,0,0,0,0,0,0],[0,0,0,0,0,0,0,0,0],[0,0,0,0,0,0,0,0,0],[0,0,0,0,0,0,0,0,0],[0,0,0,0,0,0,0,0,0],[0,0,0,0,0,0,0,0,0],[0,0,0,0,0,0,0,0,0]]}"#;
+        let body = format!("[{}]", vec![empty; 20].join(","));
+        let limits = Limits {
+            timeout: Duration::from_millis(200),
+            ..Limits::default()
+        };
+        let started = Instant::now();
+
+        let reply = handle("POST", "/batch", body.into_bytes(), limits);
+        assert_eq!((reply.status, code(&reply)), (503, "timeout"));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_busy_pool_turns_requests_away() {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let address = server.server_addr().to_ip().unwrap();
+        let limits = Limits {
+            timeout: Duration::from_millis(500),
+            workers: 1,
+            ..Limits::default()
+        };
+        thread::spawn(move || run(&server, limits));
+
+        // One request is being answered and one waits, so a third is refused.
+        let mut streams: Vec<TcpStream> = (0..3)
+            .map(|_| {
+                let mut stream = TcpStream::connect(address).unwrap();
+                write!(
+                    stream,
+                    "POST /solve HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    HARD.len(),
+                    HARD
+                )
+                .unwrap();
+                stream
+            })
+            .collect();
+        let responses: Vec<String> = streams
+            .iter_mut()
+            .map(|stream| {
+                let mut response = String::new();
+                stream.read_to_string(&mut response).unwrap();
+                response
+            })
+            .collect();
+
+        let busy = responses
+            .iter()
+            .filter(|response| response.contains(r#""code":"busy""#))
+            .count();
+        let timeouts = responses
+            .iter()
+            .filter(|response| response.contains(r#""code":"timeout""#))
+            .count();
+        assert!(busy >= 1, "{:?}", responses);
+        assert_eq!(busy + timeouts, 3, "{:?}", responses);
+    }
+
+    #[test]
+    fn test_body_limit_over_http() {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let address = server.server_addr().to_ip().unwrap();
+        let limits = Limits {
+            max_body_bytes: 16,
+            ..Limits::default()
+        };
+        let answer = thread::spawn(move || respond(server.recv().unwrap(), limits));
+
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "POST /solve HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            PUZZLE.len(),
+            PUZZLE
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        answer.join().unwrap().unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 413"), "{}", response);
+        assert!(response.contains(r#""code":"too_large""#));
+    }
+}
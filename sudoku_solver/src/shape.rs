@@ -0,0 +1,135 @@
+use anyhow::{Result, anyhow};
+
+// Board size and box dimensions. A 6x6 board with boxes of two rows by three
+// columns is `Shape::new(2, 3)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Shape {
+    size: usize,
+    box_rows: usize,
+    box_cols: usize,
+}
+
+impl Shape {
+    pub const CLASSIC: Shape = Shape {
+        size: 9,
+        box_rows: 3,
+        box_cols: 3,
+    };
+    pub const MAX_SIZE: usize = 25;
+    pub const MAX_CELLS: usize = Self::MAX_SIZE * Self::MAX_SIZE;
+
+    pub fn new(box_rows: usize, box_cols: usize) -> Result<Self> {
+        let size = box_rows * box_cols;
+        if box_rows < 2 || box_cols < 2 || size > Self::MAX_SIZE {
+            return Err(anyhow!("Unsupported box shape {}x{}", box_rows, box_cols));
+        }
+
+        Ok(Self {
+            size,
+            box_rows,
+            box_cols,
+        })
+    }
+
+    // Picks the squarest boxes, with no more rows than columns.
+    pub fn for_size(size: usize) -> Result<Self> {
+        let box_rows = (2..=size)
+            .take_while(|rows| rows * rows <= size)
+            .filter(|&rows| size.is_multiple_of(rows))
+            .last()
+            .ok_or_else(|| anyhow!("Unsupported board size {}", size))?;
+
+        Self::new(box_rows, size / box_rows)
+    }
+
+    pub fn size(self) -> usize {
+        self.size
+    }
+
+    pub fn box_rows(self) -> usize {
+        self.box_rows
+    }
+
+    pub fn box_cols(self) -> usize {
+        self.box_cols
+    }
+
+    pub fn cell_count(self) -> usize {
+        self.size * self.size
+    }
+
+    pub fn box_of(self, row: usize, col: usize) -> usize {
+        row / self.box_rows * (self.size / self.box_cols) + col / self.box_cols
+    }
+
+    // Position of the `i`-th cell of box `b`, counting row by row.
+    pub fn box_cell(self, b: usize, i: usize) -> (usize, usize) {
+        let boxes_across = self.size / self.box_cols;
+        (
+            b / boxes_across * self.box_rows + i / self.box_cols,
+            b % boxes_across * self.box_cols + i % self.box_cols,
+        )
+    }
+
+    // Digits above 9 are written as letters, `A` for 10 up to `P` for 25.
+    pub fn symbol(value: u8) -> char {
+        match value {
+            0..=9 => char::from(b'0' + value),
+            _ => char::from(b'A' + value - 10),
+        }
+    }
+
+    // `0` and `.` both mark an empty cell.
+    pub fn parse_symbol(c: char) -> Option<u8> {
+        match c {
+            '.' => Some(0),
+            '0'..='9' => Some(c as u8 - b'0'),
+            'A'..='P' => Some(c as u8 - b'A' + 10),
+            'a'..='p' => Some(c as u8 - b'a' + 10),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_size() {
+        let sizes: Vec<(usize, usize)> = [4, 6, 9, 12, 16, 25]
+            .into_iter()
+            .map(|size| {
+                let shape = Shape::for_size(size).unwrap();
+                (shape.box_rows(), shape.box_cols())
+            })
+            .collect();
+
+        assert_eq!(sizes, vec![(2, 2), (2, 3), (3, 3), (3, 4), (4, 4), (5, 5)]);
+        assert!(Shape::for_size(7).is_err(), "Prime sizes have no boxes");
+        assert!(
+            Shape::for_size(36).is_err(),
+            "Sizes above 25 are unsupported"
+        );
+    }
+
+    #[test]
+    fn test_box_of_rectangular_boxes() {
+        let shape = Shape::new(2, 3).unwrap();
+
+        assert_eq!(shape.box_of(0, 2), 0);
+        assert_eq!(shape.box_of(1, 3), 1);
+        assert_eq!(shape.box_of(2, 0), 2);
+        assert_eq!(shape.box_of(5, 5), 5);
+        assert_eq!(shape.box_cell(3, 4), (3, 4));
+    }
+
+    #[test]
+    fn test_symbols() {
+        assert_eq!(Shape::symbol(7), '7');
+        assert_eq!(Shape::symbol(16), 'G');
+        assert_eq!(Shape::parse_symbol('G'), Some(16));
+        assert_eq!(Shape::parse_symbol('.'), Some(0));
+        assert_eq!(Shape::parse_symbol('X'), None);
+    }
+}
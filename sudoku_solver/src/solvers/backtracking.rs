@@ -1,3 +1,5 @@
+use std::{cell::Cell, time::Instant};
+
 use super::solver::Solver;
 use crate::{sudoku::Sudoku, validator::Validator};
 use anyhow::Result;
@@ -6,36 +8,66 @@ pub struct BacktrackingSolver;
 
 impl Solver for BacktrackingSolver {
     fn solve(board: &mut Sudoku) -> Result<(), anyhow::Error> {
-        solve_with_validator(board, &|board, row, col, value| {
-            Validator::is_valid(board, row, col, value)
-        })
+        let solved = solve_with_validator(board, &is_valid, &|| false);
+        if !solved {
+            return Err(anyhow::anyhow!("Unsolvable board"));
+        }
+        Ok(())
+    }
+}
+
+impl BacktrackingSolver {
+    // Like `solve`, but stops searching once `deadline` passes and leaves the
+    // board as it was.
+    pub fn solve_before(board: &mut Sudoku, deadline: Instant) -> Result<()> {
+        let timed_out = Cell::new(false);
+        let solved = solve_with_validator(board, &is_valid, &|| {
+            timed_out.set(Instant::now() >= deadline);
+            timed_out.get()
+        });
+        if timed_out.get() {
+            return Err(anyhow::anyhow!("Ran out of time"));
+        }
+        if !solved {
+            return Err(anyhow::anyhow!("Unsolvable board"));
+        }
+        Ok(())
     }
 }
 
-fn solve_with_validator<F>(board: &mut Sudoku, is_valid: &F) -> Result<()>
+fn is_valid(board: &Sudoku, row: usize, col: usize, value: u8) -> bool {
+    Validator::is_valid(board, row, col, value)
+}
+
+// Reports failure as `false` so dead ends in the search do not allocate errors.
+// Once `stop` returns true every level unwinds without trying further digits.
+fn solve_with_validator<F, S>(board: &mut Sudoku, is_valid: &F, stop: &S) -> bool
 where
     F: Fn(&Sudoku, usize, usize, u8) -> bool,
+    S: Fn() -> bool,
 {
-    for row in 0..9 {
-        for col in 0..9 {
+    if stop() {
+        return false;
+    }
+    let size = board.size();
+    for row in 0..size {
+        for col in 0..size {
             if board[row][col] == 0 {
-                let mut solved = false;
-                for value in 1..=9 {
+                for value in 1..=size as u8 {
                     if is_valid(board, row, col, value) {
-                        board[row][col] = value;
-                        if solve_with_validator(board, is_valid).is_ok() {
-                            solved = true;
-                            break;
+                        board.fill_cell(row, col, value);
+                        if solve_with_validator(board, is_valid, stop) {
+                            return true;
+                        }
+                        board.fill_cell(row, col, 0);
+                        if stop() {
+                            return false;
                         }
-                        board[row][col] = 0;
                     }
                 }
-                if !solved {
-                    return Err(anyhow::anyhow!("Unsolvable board"));
-                }
-                return Ok(());
+                return false;
             }
         }
     }
-    Ok(())
+    true
 }
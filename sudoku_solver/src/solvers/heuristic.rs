@@ -6,24 +6,29 @@ pub struct Heuristic;
 
 impl Solver for Heuristic {
     fn solve(board: &mut Sudoku) -> Result<(), anyhow::Error> {
-        solve_with_heuristic(board, &|board, row, col, value| {
+        let solved = solve_with_heuristic(board, &|board, row, col, value| {
             Validator::is_valid(board, row, col, value)
-        })
+        });
+        if !solved {
+            return Err(anyhow::anyhow!("Unsolvable board"));
+        }
+        Ok(())
     }
 }
 
-fn solve_with_heuristic<F>(board: &mut Sudoku, is_valid: &F) -> Result<()>
+fn solve_with_heuristic<F>(board: &mut Sudoku, is_valid: &F) -> bool
 where
     F: Fn(&Sudoku, usize, usize, u8) -> bool,
 {
     let mut empty_cell = None;
-    let mut min_choices = 10;
+    let size = board.size();
+    let mut min_choices = size + 1;
 
-    for row in 0..9 {
-        for col in 0..9 {
+    for row in 0..size {
+        for col in 0..size {
             if board[row][col] == 0 {
                 let mut valid_choices = 0;
-                for value in 1..=9 {
+                for value in 1..=size as u8 {
                     if is_valid(board, row, col, value) {
                         valid_choices += 1;
                     }
@@ -38,17 +43,17 @@ where
     }
 
     if let Some((row, col)) = empty_cell {
-        for value in 1..=9 {
+        for value in 1..=size as u8 {
             if is_valid(board, row, col, value) {
-                board[row][col] = value;
-                if solve_with_heuristic(board, is_valid).is_ok() {
-                    return Ok(());
+                board.fill_cell(row, col, value);
+                if solve_with_heuristic(board, is_valid) {
+                    return true;
                 }
-                board[row][col] = 1;
+                board.fill_cell(row, col, 0);
             }
         }
-        return Err(anyhow::anyhow!("Unsolvable board"));
+        return false;
     }
 
-    Ok(())
+    true
 }
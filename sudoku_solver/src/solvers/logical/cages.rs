@@ -0,0 +1,248 @@
+use super::{Candidate, Step, Technique, Unit};
+use crate::{
+    candidate_grid::{CandidateGrid, digits},
+    constraint::Constraint,
+};
+
+// Cages with more empty cells are left to the other techniques, since the
+// number of digit sets to try grows combinatorially with the cell count.
+const MAX_EMPTY_CELLS: usize = 9;
+
+// Removes candidates that appear in no combination of distinct digits
+// adding up to the cage sum.
+pub(super) fn find_cage_combination(grid: &CandidateGrid) -> Option<Step> {
+    let cages = grid
+        .constraints()
+        .iter()
+        .filter_map(|constraint| match constraint {
+            Constraint::Cage { sum, cells } => Some((*sum, cells)),
+            _ => None,
+        });
+
+    for (index, (sum, cells)) in cages.enumerate() {
+        let cells: Vec<usize> = cells
+            .iter()
+            .map(|&(row, col)| grid.cell(row, col))
+            .collect();
+        let (mut used, mut total) = (0u32, 0u32);
+        for &cell in &cells {
+            used |= 1 << grid.value(cell);
+            total += grid.value(cell) as u32;
+        }
+        let empty: Vec<usize> = cells
+            .iter()
+            .copied()
+            .filter(|&cell| grid.value(cell) == 0)
+            .collect();
+        if empty.is_empty() || empty.len() > MAX_EMPTY_CELLS || total > sum {
+            continue;
+        }
+
+        let free: Vec<u8> = grid
+            .all_digits()
+            .filter(|digit| used & (1 << digit) == 0)
+            .collect();
+        let candidates: Vec<u32> = empty.iter().map(|&cell| grid.candidates(cell)).collect();
+        let mut possible = vec![0u32; empty.len()];
+        collect_combinations(
+            &free,
+            empty.len(),
+            sum - total,
+            0,
+            &candidates,
+            &mut possible,
+        );
+
+        let eliminations: Vec<Candidate> = empty
+            .iter()
+            .zip(&possible)
+            .flat_map(|(&cell, &mask)| {
+                grid.candidate_digits(cell)
+                    .filter(move |digit| mask & (1 << digit) == 0)
+                    .map(move |digit| Candidate::new(grid, cell, digit))
+            })
+            .collect();
+        if !eliminations.is_empty() {
+            return Some(Step {
+                technique: Technique::CageCombination,
+                placements: Vec::new(),
+                eliminations,
+                units: vec![Unit::Cage(index)],
+                chains: Vec::new(),
+            });
+        }
+    }
+    None
+}
+
+// Walks the sets of `count` digits from `free`, in increasing order, that add
+// up to `remaining`, and records per cell the digits it takes in some
+// assignment of the set to the cells. A branch is cut once the digits left
+// can no longer reach `remaining`.
+fn collect_combinations(
+    free: &[u8],
+    count: usize,
+    remaining: u32,
+    set: u32,
+    candidates: &[u32],
+    possible: &mut [u32],
+) {
+    if count == 0 {
+        if remaining == 0 {
+            record_assignments(set, candidates, possible);
+        }
+        return;
+    }
+    if free.len() < count {
+        return;
+    }
+    let lowest: u32 = free.iter().take(count).map(|&digit| digit as u32).sum();
+    let highest: u32 = free
+        .iter()
+        .rev()
+        .take(count)
+        .map(|&digit| digit as u32)
+        .sum();
+    if remaining < lowest || remaining > highest {
+        return;
+    }
+
+    for (index, &digit) in free.iter().enumerate() {
+        if digit as u32 > remaining {
+            break;
+        }
+        collect_combinations(
+            &free[index + 1..],
+            count - 1,
+            remaining - digit as u32,
+            set | 1 << digit,
+            candidates,
+            possible,
+        );
+    }
+}
+
+// Marks every candidate in `set` that some one-to-one assignment of the set
+// to the cells uses.
+fn record_assignments(set: u32, candidates: &[u32], possible: &mut [u32]) {
+    let allowed: Vec<u32> = candidates.iter().map(|&mask| mask & set).collect();
+    for cell in 0..allowed.len() {
+        for digit in digits(allowed[cell] & !possible[cell]) {
+            let mut forced = allowed.clone();
+            forced[cell] = 1 << digit;
+            if let Some(assignment) = assign(&forced) {
+                for (mask, digit) in possible.iter_mut().zip(assignment) {
+                    *mask |= 1 << digit;
+                }
+            }
+        }
+    }
+}
+
+// Gives each cell a distinct digit from its `allowed` mask, if that can be
+// done, by growing a bipartite matching one cell at a time.
+fn assign(allowed: &[u32]) -> Option<Vec<u8>> {
+    let mut owners = [None; 32];
+    for cell in 0..allowed.len() {
+        if !augment(allowed, cell, &mut 0, &mut owners) {
+            return None;
+        }
+    }
+    let mut assignment = vec![0u8; allowed.len()];
+    for (digit, owner) in owners.iter().enumerate() {
+        if let Some(cell) = owner {
+            assignment[*cell] = digit as u8;
+        }
+    }
+    Some(assignment)
+}
+
+// Finds `cell` a digit, moving earlier cells to other digits where needed.
+fn augment(allowed: &[u32], cell: usize, seen: &mut u32, owners: &mut [Option<usize>; 32]) -> bool {
+    for digit in digits(allowed[cell] & !*seen) {
+        *seen |= 1 << digit;
+        let digit = digit as usize;
+        if owners[digit].is_none_or(|owner| augment(allowed, owner, seen, owners)) {
+            owners[digit] = Some(cell);
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::Sudoku;
+
+    #[test]
+    fn test_find_cage_combination() {
+        let sudoku = Sudoku::from_string(&"0".repeat(81))
+            .unwrap()
+            .with_constraints(vec![Constraint::Cage {
+                sum: 4,
+                cells: vec![(0, 0), (0, 1)],
+            }])
+            .unwrap();
+        let mut grid = CandidateGrid::from_sudoku(&sudoku);
+        grid.eliminate(0, 3);
+
+        let step = find_cage_combination(&grid).expect("Expected a cage combination");
+        assert_eq!(step.units, vec![Unit::Cage(0)]);
+        assert_eq!(
+            step.eliminations,
+            vec![
+                Candidate::new(&grid, 0, 2),
+                Candidate::new(&grid, 1, 1),
+                Candidate::new(&grid, 1, 2)
+            ],
+            "Only 1 + 3 is left once r1c1 loses 3"
+        );
+        assert_eq!(
+            step.to_string(),
+            "Cage Combination (cage 1): r1c1<>2, r1c2<>1, r1c2<>2"
+        );
+    }
+
+    #[test]
+    fn test_large_cages_stay_fast() {
+        let row = |cells: usize| (0..cells).map(|col| (0, col)).collect::<Vec<_>>();
+        let board = || Sudoku::from_string(&"0".repeat(256)).unwrap();
+
+        // Only 1 to 8 add up to 36, and r1c8 can only be 8 once the others
+        // lose it.
+        let low = board()
+            .with_constraints(vec![Constraint::Cage {
+                sum: 36,
+                cells: row(8),
+            }])
+            .unwrap();
+        let mut grid = CandidateGrid::from_sudoku(&low);
+        for cell in 0..7 {
+            grid.eliminate(cell, 8);
+        }
+        let step = find_cage_combination(&grid).expect("Expected a cage combination");
+        assert_eq!(step.eliminations.len(), 7);
+
+        // A nine-cell cage in the middle of the sum range has thousands of
+        // digit sets.
+        let mid = board()
+            .with_constraints(vec![Constraint::Cage {
+                sum: 100,
+                cells: row(9),
+            }])
+            .unwrap();
+        let started = std::time::Instant::now();
+        assert!(find_cage_combination(&CandidateGrid::from_sudoku(&mid)).is_none());
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+
+        // A cage over a whole row is past the cell limit and skipped.
+        let full = board()
+            .with_constraints(vec![Constraint::Cage {
+                sum: 136,
+                cells: row(16),
+            }])
+            .unwrap();
+        assert!(find_cage_combination(&CandidateGrid::from_sudoku(&full)).is_none());
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::VecDeque;
+
+use super::{Candidate, Chain, Link, Step, Technique};
+use crate::candidate_grid::{CandidateGrid, digits};
+
+type Node = (usize, u8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Links {
+    SingleDigit,
+    Bivalue,
+    Any,
+}
+
+pub(super) fn find_x_cycle(grid: &CandidateGrid) -> Option<Step> {
+    search(grid, Links::SingleDigit, |path| {
+        let (start, end) = (path[0], path[path.len() - 1]);
+        if start != end && grid.sees(start.0, end.0) {
+            return loop_step(grid, path);
+        }
+        chain_step(grid, Technique::XCycle, path)
+    })
+}
+
+pub(super) fn find_xy_chain(grid: &CandidateGrid) -> Option<Step> {
+    search(grid, Links::Bivalue, |path| {
+        chain_step(grid, Technique::XYChain, path)
+    })
+}
+
+pub(super) fn find_aic(grid: &CandidateGrid) -> Option<Step> {
+    search(grid, Links::Any, |path| {
+        chain_step(grid, Technique::AlternatingInferenceChain, path)
+    })
+}
+
+// Breadth-first search over alternating chains. Each path starts with a strong
+// link out of an "off" candidate and ends with a strong link into an "on"
+// candidate, so either the first or the last candidate of the path is true.
+fn search<F>(grid: &CandidateGrid, links: Links, conclude: F) -> Option<Step>
+where
+    F: Fn(&[Node]) -> Option<Step>,
+{
+    for cell in 0..grid.cell_count() {
+        for digit in digits(grid.candidates(cell)) {
+            let root = (cell, digit);
+            let mut parents = vec![[None; 2]; grid.cell_count() * (grid.size() + 1)];
+            let mut queue = VecDeque::from([(root, false)]);
+            parents[index(grid, root)][0] = Some(root);
+
+            while let Some((node, on)) = queue.pop_front() {
+                if on {
+                    let path = reconstruct(grid, &parents, node);
+                    if path.len() >= 4
+                        && let Some(step) = conclude(&path)
+                    {
+                        return Some(step);
+                    }
+                }
+
+                let next = if on {
+                    weak_links(grid, node, links)
+                } else {
+                    strong_links(grid, node, links)
+                };
+                for neighbour in next {
+                    let state = &mut parents[index(grid, neighbour)][usize::from(!on)];
+                    if state.is_none() {
+                        *state = Some(node);
+                        queue.push_back((neighbour, !on));
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+fn index(grid: &CandidateGrid, (cell, digit): Node) -> usize {
+    cell * (grid.size() + 1) + digit as usize
+}
+
+fn reconstruct(grid: &CandidateGrid, parents: &[[Option<Node>; 2]], end: Node) -> Vec<Node> {
+    let mut path = vec![end];
+    let mut on = true;
+    let mut node = end;
+
+    while let Some(parent) = parents[index(grid, node)][usize::from(on)] {
+        if parent == node && !on {
+            break;
+        }
+        path.push(parent);
+        node = parent;
+        on = !on;
+    }
+
+    path.reverse();
+    path
+}
+
+fn strong_links(grid: &CandidateGrid, (cell, digit): Node, links: Links) -> Vec<Node> {
+    let mut result = Vec::new();
+
+    if links != Links::Bivalue {
+        for unit in grid.units_of(cell) {
+            let mut others = grid
+                .unit_cells(unit)
+                .iter()
+                .copied()
+                .filter(|&other| other != cell && grid.has_candidate(other, digit));
+            if let (Some(other), None) = (others.next(), others.next())
+                && !result.contains(&(other, digit))
+            {
+                result.push((other, digit));
+            }
+        }
+    }
+
+    if links != Links::SingleDigit {
+        let mask = grid.candidates(cell);
+        if mask.count_ones() == 2 {
+            result.extend(digits(mask & !(1 << digit)).map(|other| (cell, other)));
+        }
+    }
+
+    result
+}
+
+fn weak_links(grid: &CandidateGrid, (cell, digit): Node, links: Links) -> Vec<Node> {
+    let mut result: Vec<Node> = grid
+        .peers(cell)
+        .iter()
+        .copied()
+        .filter(|&peer| grid.has_candidate(peer, digit))
+        .map(|peer| (peer, digit))
+        .collect();
+
+    if links == Links::Any {
+        result.extend(digits(grid.candidates(cell) & !(1 << digit)).map(|other| (cell, other)));
+    }
+
+    result
+}
+
+fn to_chain(grid: &CandidateGrid, path: &[Node]) -> Chain {
+    let links = path[1..]
+        .iter()
+        .enumerate()
+        .map(|(i, &(cell, digit))| {
+            let link = if i % 2 == 0 { Link::Strong } else { Link::Weak };
+            (link, Candidate::new(grid, cell, digit))
+        })
+        .collect();
+
+    Chain {
+        start: Candidate::new(grid, path[0].0, path[0].1),
+        links,
+    }
+}
+
+// Either the first or the last candidate of the chain is true.
+fn chain_step(grid: &CandidateGrid, technique: Technique, path: &[Node]) -> Option<Step> {
+    let (start, end) = (path[0], path[path.len() - 1]);
+    let mut placements = Vec::new();
+    let mut eliminations = Vec::new();
+
+    if start == end {
+        placements.push(Candidate::new(grid, start.0, start.1));
+    } else if start.1 == end.1 {
+        eliminations = seen_by_both(grid, start.1, start.0, end.0, &[]);
+    } else if start.0 == end.0 {
+        eliminations = digits(grid.candidates(start.0) & !(1 << start.1) & !(1 << end.1))
+            .map(|digit| Candidate::new(grid, start.0, digit))
+            .collect();
+    } else if grid.sees(start.0, end.0) {
+        eliminations = [(start.0, end.1), (end.0, start.1)]
+            .into_iter()
+            .filter(|&(cell, digit)| grid.has_candidate(cell, digit))
+            .map(|(cell, digit)| Candidate::new(grid, cell, digit))
+            .collect();
+    }
+
+    if placements.is_empty() && eliminations.is_empty() {
+        return None;
+    }
+    Some(Step {
+        technique,
+        placements,
+        eliminations,
+        units: Vec::new(),
+        chains: vec![to_chain(grid, path)],
+    })
+}
+
+// A continuous loop: every weak link of the loop becomes strong, so the digit
+// can be removed from any other cell that sees both ends of a weak link.
+fn loop_step(grid: &CandidateGrid, path: &[Node]) -> Option<Step> {
+    let digit = path[0].1;
+    let cells: Vec<usize> = path.iter().map(|&(cell, _)| cell).collect();
+    let mut eliminations: Vec<Candidate> = Vec::new();
+
+    let weak_pairs = path[1..]
+        .chunks(2)
+        .filter(|pair| pair.len() == 2)
+        .map(|pair| (pair[0].0, pair[1].0))
+        .chain([(path[path.len() - 1].0, path[0].0)]);
+    for (a, b) in weak_pairs {
+        for candidate in seen_by_both(grid, digit, a, b, &cells) {
+            if !eliminations.contains(&candidate) {
+                eliminations.push(candidate);
+            }
+        }
+    }
+
+    if eliminations.is_empty() {
+        return None;
+    }
+
+    let mut chain = to_chain(grid, path);
+    chain
+        .links
+        .push((Link::Weak, Candidate::new(grid, path[0].0, digit)));
+    Some(Step {
+        technique: Technique::XCycle,
+        placements: Vec::new(),
+        eliminations,
+        units: Vec::new(),
+        chains: vec![chain],
+    })
+}
+
+fn seen_by_both(
+    grid: &CandidateGrid,
+    digit: u8,
+    a: usize,
+    b: usize,
+    excluded: &[usize],
+) -> Vec<Candidate> {
+    (0..grid.cell_count())
+        .filter(|cell| *cell != a && *cell != b && !excluded.contains(cell))
+        .filter(|&cell| grid.has_candidate(cell, digit) && grid.sees(cell, a) && grid.sees(cell, b))
+        .map(|cell| Candidate::new(grid, cell, digit))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::Sudoku;
+
+    fn empty_grid() -> CandidateGrid {
+        CandidateGrid::from_sudoku(&Sudoku::from_string(&"0".repeat(81)).unwrap())
+    }
+
+    fn keep_only(grid: &mut CandidateGrid, cell: usize, keep: &[u8]) {
+        for digit in (1..=9).filter(|digit| !keep.contains(digit)) {
+            grid.eliminate(cell, digit);
+        }
+    }
+
+    fn restrict_box_zero(grid: &mut CandidateGrid) {
+        for cell in [1, 2, 9, 10, 11, 18, 20] {
+            grid.eliminate(cell, 1);
+        }
+    }
+
+    #[test]
+    fn test_find_x_cycle() {
+        let mut grid = empty_grid();
+        restrict_box_zero(&mut grid);
+        for row in (0..9).filter(|&row| row != 2 && row != 6) {
+            grid.eliminate(row * 9 + 6, 1);
+        }
+
+        let step = find_x_cycle(&grid).expect("Expected an X-Cycle");
+        assert_eq!(step.eliminations, vec![Candidate::new(&grid, 54, 1)]);
+        assert_eq!(
+            step.chains[0].to_string(),
+            "(1)r1c1=(1)r3c2-(1)r3c7=(1)r7c7"
+        );
+    }
+
+    #[test]
+    fn test_find_xy_chain() {
+        let mut grid = empty_grid();
+        keep_only(&mut grid, 0, &[1, 2]);
+        keep_only(&mut grid, 4, &[2, 3]);
+        keep_only(&mut grid, 40, &[3, 4]);
+        keep_only(&mut grid, 44, &[4, 1]);
+
+        let step = find_xy_chain(&grid).expect("Expected an XY-Chain");
+        assert_eq!(
+            step.eliminations,
+            vec![Candidate::new(&grid, 8, 1), Candidate::new(&grid, 36, 1)]
+        );
+        assert_eq!(
+            step.chains[0].to_string(),
+            "(1)r1c1=(2)r1c1-(2)r1c5=(3)r1c5-(3)r5c5=(4)r5c5-(4)r5c9=(1)r5c9"
+        );
+    }
+
+    #[test]
+    fn test_find_aic() {
+        let mut grid = empty_grid();
+        restrict_box_zero(&mut grid);
+        keep_only(&mut grid, 25, &[1, 5]);
+        keep_only(&mut grid, 61, &[1, 5]);
+
+        let step = find_aic(&grid).expect("Expected an AIC");
+        assert_eq!(
+            step.eliminations,
+            vec![Candidate::new(&grid, 7, 1), Candidate::new(&grid, 54, 1)]
+        );
+        assert_eq!(
+            step.chains[0].to_string(),
+            "(1)r1c1=(1)r3c2-(1)r3c8=(5)r3c8-(5)r7c8=(1)r7c8"
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::VecDeque;
+
+use super::{Candidate, Chain, Link, Step, Technique};
+use crate::candidate_grid::CandidateGrid;
+
+pub(super) fn find_simple_coloring(grid: &CandidateGrid) -> Option<Step> {
+    for digit in grid.all_digits() {
+        let mut visited = vec![false; grid.cell_count()];
+
+        for root in 0..grid.cell_count() {
+            if visited[root] || conjugates(grid, root, digit).is_empty() {
+                continue;
+            }
+
+            let (cells, colors, parents) = color_component(grid, root, digit);
+            for &cell in &cells {
+                visited[cell] = true;
+            }
+
+            if let Some(step) = color_wrap(grid, &cells, &colors, &parents, digit) {
+                return Some(step);
+            }
+            if let Some(step) = color_trap(grid, &cells, &colors, &parents, digit) {
+                return Some(step);
+            }
+        }
+    }
+    None
+}
+
+fn conjugates(grid: &CandidateGrid, cell: usize, digit: u8) -> Vec<usize> {
+    if !grid.has_candidate(cell, digit) {
+        return Vec::new();
+    }
+
+    let mut result = Vec::new();
+    for unit in grid.units_of(cell) {
+        let mut others = grid
+            .unit_cells(unit)
+            .iter()
+            .copied()
+            .filter(|&other| other != cell && grid.has_candidate(other, digit));
+        if let (Some(other), None) = (others.next(), others.next())
+            && !result.contains(&other)
+        {
+            result.push(other);
+        }
+    }
+    result
+}
+
+fn color_component(
+    grid: &CandidateGrid,
+    root: usize,
+    digit: u8,
+) -> (Vec<usize>, Vec<Option<bool>>, Vec<Option<usize>>) {
+    let mut cells = vec![root];
+    let mut colors = vec![None; grid.cell_count()];
+    let mut parents = vec![None; grid.cell_count()];
+    let mut queue = VecDeque::from([root]);
+    colors[root] = Some(false);
+
+    while let Some(cell) = queue.pop_front() {
+        let color = colors[cell] == Some(true);
+        for other in conjugates(grid, cell, digit) {
+            if colors[other].is_none() {
+                colors[other] = Some(!color);
+                parents[other] = Some(cell);
+                cells.push(other);
+                queue.push_back(other);
+            }
+        }
+    }
+
+    (cells, colors, parents)
+}
+
+// Two cells of the same color see each other, so that color is false.
+fn color_wrap(
+    grid: &CandidateGrid,
+    cells: &[usize],
+    colors: &[Option<bool>],
+    parents: &[Option<usize>],
+    digit: u8,
+) -> Option<Step> {
+    for (i, &a) in cells.iter().enumerate() {
+        for &b in &cells[i + 1..] {
+            if colors[a] != colors[b] || !grid.sees(a, b) {
+                continue;
+            }
+
+            let eliminations = cells
+                .iter()
+                .filter(|&&cell| colors[cell] == colors[a])
+                .map(|&cell| Candidate::new(grid, cell, digit))
+                .collect();
+            return Some(Step {
+                technique: Technique::SimpleColoring,
+                placements: Vec::new(),
+                eliminations,
+                units: Vec::new(),
+                chains: vec![color_chain(grid, parents, a, b, digit)],
+            });
+        }
+    }
+    None
+}
+
+// A cell outside the component that sees both colors cannot hold the digit.
+fn color_trap(
+    grid: &CandidateGrid,
+    cells: &[usize],
+    colors: &[Option<bool>],
+    parents: &[Option<usize>],
+    digit: u8,
+) -> Option<Step> {
+    let mut eliminations = Vec::new();
+    let mut chains = Vec::new();
+
+    for cell in (0..grid.cell_count())
+        .filter(|&cell| colors[cell].is_none() && grid.has_candidate(cell, digit))
+    {
+        let seen = |color| {
+            cells
+                .iter()
+                .copied()
+                .find(|&other| colors[other] == Some(color) && grid.sees(cell, other))
+        };
+        if let (Some(a), Some(b)) = (seen(false), seen(true)) {
+            eliminations.push(Candidate::new(grid, cell, digit));
+            chains.push(color_chain(grid, parents, a, b, digit));
+        }
+    }
+
+    (!eliminations.is_empty()).then_some(Step {
+        technique: Technique::SimpleColoring,
+        placements: Vec::new(),
+        eliminations,
+        units: Vec::new(),
+        chains,
+    })
+}
+
+fn color_chain(
+    grid: &CandidateGrid,
+    parents: &[Option<usize>],
+    a: usize,
+    b: usize,
+    digit: u8,
+) -> Chain {
+    let ancestors = |mut cell: usize| {
+        let mut path = vec![cell];
+        while let Some(parent) = parents[cell] {
+            path.push(parent);
+            cell = parent;
+        }
+        path
+    };
+
+    let from_a = ancestors(a);
+    let from_b = ancestors(b);
+    let meet = from_b
+        .iter()
+        .position(|cell| from_a.contains(cell))
+        .expect("Cells of one component share the root");
+    let mut path: Vec<usize> = from_a
+        .into_iter()
+        .take_while(|&cell| cell != from_b[meet])
+        .collect();
+    path.extend(from_b[..=meet].iter().rev());
+
+    Chain {
+        start: Candidate::new(grid, path[0], digit),
+        links: path[1..]
+            .iter()
+            .map(|&cell| (Link::Strong, Candidate::new(grid, cell, digit)))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::Sudoku;
+
+    #[test]
+    fn test_find_simple_coloring_trap() {
+        let mut grid = CandidateGrid::from_sudoku(&Sudoku::from_string(&"0".repeat(81)).unwrap());
+        for cell in [1, 2, 9, 10, 11, 18, 20, 21, 22, 23, 25, 26] {
+            grid.eliminate(cell, 1);
+        }
+        for row in (0..9).filter(|&row| row != 2 && row != 6) {
+            grid.eliminate(row * 9 + 6, 1);
+        }
+
+        let step = find_simple_coloring(&grid).expect("Expected simple coloring");
+        assert_eq!(step.eliminations, vec![Candidate::new(&grid, 54, 1)]);
+        assert_eq!(
+            step.chains[0].to_string(),
+            "(1)r1c1=(1)r3c2=(1)r3c7=(1)r7c7"
+        );
+    }
+
+    #[test]
+    fn test_find_simple_coloring_wrap() {
+        let mut grid = CandidateGrid::from_sudoku(&Sudoku::from_string(&"0".repeat(81)).unwrap());
+        // r1c1=r1c5=r5c5=r5c2=r2c2 puts r1c1 and r2c2 on the same color inside box 1.
+        for cell in (0..9).filter(|&cell| cell != 0 && cell != 4) {
+            grid.eliminate(cell, 1);
+        }
+        for row in (0..9).filter(|&row| row != 0 && row != 4) {
+            grid.eliminate(row * 9 + 4, 1);
+        }
+        for col in (0..9).filter(|&col| col != 4 && col != 1) {
+            grid.eliminate(36 + col, 1);
+        }
+        for row in (0..9).filter(|&row| row != 4 && row != 1) {
+            grid.eliminate(row * 9 + 1, 1);
+        }
+
+        let step = find_simple_coloring(&grid).expect("Expected simple coloring");
+        assert_eq!(
+            step.eliminations,
+            vec![
+                Candidate::new(&grid, 0, 1),
+                Candidate::new(&grid, 40, 1),
+                Candidate::new(&grid, 10, 1)
+            ]
+        );
+    }
+}
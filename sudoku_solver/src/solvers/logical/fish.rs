@@ -0,0 +1,263 @@
+use super::{Candidate, Step, Technique, combinations};
+use crate::candidate_grid::{CandidateGrid, Unit};
+
+#[derive(Debug, Clone, Copy)]
+enum Orientation {
+    Rows,
+    Columns,
+}
+
+impl Orientation {
+    fn base(self, line: usize) -> Unit {
+        match self {
+            Orientation::Rows => Unit::Row(line),
+            Orientation::Columns => Unit::Column(line),
+        }
+    }
+
+    fn cover(self, line: usize) -> Unit {
+        match self {
+            Orientation::Rows => Unit::Column(line),
+            Orientation::Columns => Unit::Row(line),
+        }
+    }
+
+    fn cell(self, grid: &CandidateGrid, base: usize, cover: usize) -> usize {
+        match self {
+            Orientation::Rows => grid.cell(base, cover),
+            Orientation::Columns => grid.cell(cover, base),
+        }
+    }
+}
+
+pub(super) fn find_basic_fish(
+    grid: &CandidateGrid,
+    size: usize,
+    technique: Technique,
+) -> Option<Step> {
+    for digit in grid.all_digits() {
+        for orientation in [Orientation::Rows, Orientation::Columns] {
+            let bases: Vec<usize> = (0..grid.size())
+                .filter(|&base| {
+                    let count = positions(grid, orientation, base, digit).count_ones() as usize;
+                    (2..=size).contains(&count)
+                })
+                .collect();
+
+            for base_set in combinations(&bases, size) {
+                let cover = base_set.iter().fold(0, |mask, &base| {
+                    mask | positions(grid, orientation, base, digit)
+                });
+                if cover.count_ones() as usize != size {
+                    continue;
+                }
+
+                let eliminations =
+                    cover_eliminations(grid, orientation, &base_set, cover, digit, |_| true);
+                if !eliminations.is_empty() {
+                    return Some(fish_step(
+                        grid,
+                        technique,
+                        orientation,
+                        &base_set,
+                        cover,
+                        eliminations,
+                    ));
+                }
+            }
+        }
+    }
+    None
+}
+
+pub(super) fn find_finned_fish(
+    grid: &CandidateGrid,
+    size: usize,
+    sashimi: bool,
+    technique: Technique,
+) -> Option<Step> {
+    for digit in grid.all_digits() {
+        for orientation in [Orientation::Rows, Orientation::Columns] {
+            let bases: Vec<usize> = (0..grid.size())
+                .filter(|&base| positions(grid, orientation, base, digit) != 0)
+                .collect();
+
+            for base_set in combinations(&bases, size) {
+                let union = base_set.iter().fold(0, |mask, &base| {
+                    mask | positions(grid, orientation, base, digit)
+                });
+                if union.count_ones() as usize <= size {
+                    continue;
+                }
+
+                let lines: Vec<usize> = (0..grid.size())
+                    .filter(|line| union & (1 << line) != 0)
+                    .collect();
+                for cover_set in combinations(&lines, size) {
+                    let cover = cover_set.iter().fold(0, |mask, &line| mask | 1 << line);
+                    let covered: Vec<u32> = base_set
+                        .iter()
+                        .map(|&base| {
+                            (positions(grid, orientation, base, digit) & cover).count_ones()
+                        })
+                        .collect();
+                    if covered.contains(&0) || covered.contains(&1) != sashimi {
+                        continue;
+                    }
+
+                    let fins: Vec<usize> = base_set
+                        .iter()
+                        .flat_map(|&base| {
+                            let outside = positions(grid, orientation, base, digit) & !cover;
+                            (0..grid.size())
+                                .filter(move |line| outside & (1 << line) != 0)
+                                .map(move |line| orientation.cell(grid, base, line))
+                        })
+                        .collect();
+                    let fin_box = grid.box_of(fins[0]);
+                    if fins.iter().any(|&fin| grid.box_of(fin) != fin_box) {
+                        continue;
+                    }
+
+                    let eliminations =
+                        cover_eliminations(grid, orientation, &base_set, cover, digit, |cell| {
+                            grid.box_of(cell) == fin_box
+                        });
+                    if !eliminations.is_empty() {
+                        return Some(fish_step(
+                            grid,
+                            technique,
+                            orientation,
+                            &base_set,
+                            cover,
+                            eliminations,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+fn positions(grid: &CandidateGrid, orientation: Orientation, base: usize, digit: u8) -> u32 {
+    (0..grid.size())
+        .filter(|&line| grid.has_candidate(orientation.cell(grid, base, line), digit))
+        .fold(0, |mask, line| mask | 1 << line)
+}
+
+fn cover_eliminations<F>(
+    grid: &CandidateGrid,
+    orientation: Orientation,
+    base_set: &[usize],
+    cover: u32,
+    digit: u8,
+    allowed: F,
+) -> Vec<Candidate>
+where
+    F: Fn(usize) -> bool,
+{
+    (0..grid.size())
+        .filter(|line| cover & (1 << line) != 0)
+        .flat_map(|line| {
+            (0..grid.size())
+                .filter(|base| !base_set.contains(base))
+                .map(move |base| orientation.cell(grid, base, line))
+        })
+        .filter(|&cell| allowed(cell) && grid.has_candidate(cell, digit))
+        .map(|cell| Candidate::new(grid, cell, digit))
+        .collect()
+}
+
+fn fish_step(
+    grid: &CandidateGrid,
+    technique: Technique,
+    orientation: Orientation,
+    base_set: &[usize],
+    cover: u32,
+    eliminations: Vec<Candidate>,
+) -> Step {
+    let units = base_set
+        .iter()
+        .map(|&base| orientation.base(base))
+        .chain(
+            (0..grid.size())
+                .filter(|line| cover & (1 << line) != 0)
+                .map(|line| orientation.cover(line)),
+        )
+        .collect();
+
+    Step {
+        technique,
+        placements: Vec::new(),
+        eliminations,
+        units,
+        chains: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::Sudoku;
+
+    fn grid_with_row_positions(rows: &[(usize, &[usize])], digit: u8) -> CandidateGrid {
+        let mut grid = CandidateGrid::from_sudoku(&Sudoku::from_string(&"0".repeat(81)).unwrap());
+        for &(row, cols) in rows {
+            for col in (0..9).filter(|col| !cols.contains(col)) {
+                grid.eliminate(row * 9 + col, digit);
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn test_find_x_wing() {
+        let grid = grid_with_row_positions(&[(0, &[0, 4]), (4, &[0, 4])], 1);
+
+        let step = find_basic_fish(&grid, 2, Technique::XWing).expect("Expected an X-Wing");
+        assert_eq!(
+            step.units,
+            vec![Unit::Row(0), Unit::Row(4), Unit::Column(0), Unit::Column(4)]
+        );
+        assert_eq!(step.eliminations.len(), 14);
+        assert!(step.eliminations.iter().all(|c| c.col == 0 || c.col == 4));
+        assert!(step.eliminations.iter().all(|c| c.row != 0 && c.row != 4));
+    }
+
+    #[test]
+    fn test_find_swordfish() {
+        let grid = grid_with_row_positions(&[(1, &[1, 7]), (4, &[1, 4]), (7, &[4, 7])], 5);
+
+        assert!(find_basic_fish(&grid, 2, Technique::XWing).is_none());
+        let step = find_basic_fish(&grid, 3, Technique::Swordfish).expect("Expected a Swordfish");
+        assert!(step.eliminations.contains(&Candidate::new(&grid, 4, 5)));
+        assert!(step.eliminations.iter().all(|c| [1, 4, 7].contains(&c.col)));
+    }
+
+    #[test]
+    fn test_find_finned_x_wing() {
+        let grid = grid_with_row_positions(&[(0, &[0, 4, 5]), (4, &[0, 4])], 1);
+
+        assert!(find_finned_fish(&grid, 2, true, Technique::SashimiXWing).is_none());
+        let step = find_finned_fish(&grid, 2, false, Technique::FinnedXWing)
+            .expect("Expected a finned X-Wing");
+        assert_eq!(
+            step.eliminations,
+            vec![Candidate::new(&grid, 13, 1), Candidate::new(&grid, 22, 1)]
+        );
+    }
+
+    #[test]
+    fn test_find_sashimi_x_wing() {
+        let grid = grid_with_row_positions(&[(0, &[4, 5]), (4, &[0, 4])], 1);
+
+        assert!(find_finned_fish(&grid, 2, false, Technique::FinnedXWing).is_none());
+        let step = find_finned_fish(&grid, 2, true, Technique::SashimiXWing)
+            .expect("Expected a sashimi X-Wing");
+        assert_eq!(
+            step.eliminations,
+            vec![Candidate::new(&grid, 13, 1), Candidate::new(&grid, 22, 1)]
+        );
+    }
+}
@@ -0,0 +1,251 @@
+use super::{Candidate, Chain, Link, Step, Technique};
+use crate::candidate_grid::{CandidateGrid, Unit, digits};
+
+// The consequences of assuming one candidate true, propagated with singles.
+struct Branch {
+    grid: CandidateGrid,
+    causes: Vec<Option<usize>>,
+    removed_by: Vec<Option<usize>>,
+    order: Vec<usize>,
+    contradiction: bool,
+}
+
+impl Branch {
+    fn assume(grid: &CandidateGrid, cell: usize, digit: u8) -> Self {
+        let mut branch = Self {
+            grid: grid.clone(),
+            causes: vec![None; grid.cell_count()],
+            removed_by: vec![None; grid.cell_count() * (grid.size() + 1)],
+            order: vec![0; grid.cell_count()],
+            contradiction: false,
+        };
+        branch.place(cell, digit, None, 1);
+
+        for step in 2..=grid.cell_count() {
+            if branch.grid.has_contradiction() || has_unit_contradiction(&branch.grid) {
+                branch.contradiction = true;
+                break;
+            }
+            match branch.next_single() {
+                Some((cell, digit, cause)) => branch.place(cell, digit, cause, step),
+                None => break,
+            }
+        }
+        branch
+    }
+
+    fn place(&mut self, cell: usize, digit: u8, cause: Option<usize>, step: usize) {
+        for i in 0..self.grid.peers(cell).len() {
+            let peer = self.grid.peers(cell)[i];
+            if self.grid.has_candidate(peer, digit) {
+                let index = self.index(peer, digit);
+                self.removed_by[index] = Some(cell);
+            }
+        }
+        self.grid.place(cell, digit);
+        self.causes[cell] = cause;
+        self.order[cell] = step;
+    }
+
+    fn next_single(&self) -> Option<(usize, u8, Option<usize>)> {
+        for cell in 0..self.grid.cell_count() {
+            let mask = self.grid.candidates(cell);
+            if mask.count_ones() == 1 {
+                let removed = self.grid.all_digits().map(|digit| (cell, digit));
+                return Some((cell, mask.trailing_zeros() as u8, self.latest(removed)));
+            }
+        }
+
+        for unit in self.grid.units() {
+            let unit_cells = self.grid.unit_cells(unit);
+            for digit in self.grid.all_digits() {
+                let mut cells = unit_cells
+                    .iter()
+                    .copied()
+                    .filter(|&cell| self.grid.has_candidate(cell, digit));
+                if let (Some(cell), None) = (cells.next(), cells.next()) {
+                    let removed = unit_cells.iter().map(|&other| (other, digit));
+                    return Some((cell, digit, self.latest(removed)));
+                }
+            }
+        }
+        None
+    }
+
+    // The most recent placement that removed one of the given candidates.
+    fn latest(&self, candidates: impl Iterator<Item = (usize, u8)>) -> Option<usize> {
+        candidates
+            .filter_map(|(cell, digit)| self.removed_by[self.index(cell, digit)])
+            .max_by_key(|&placed| self.order[placed])
+    }
+
+    fn index(&self, cell: usize, digit: u8) -> usize {
+        cell * (self.grid.size() + 1) + digit as usize
+    }
+
+    fn excludes(&self, cell: usize, digit: u8) -> bool {
+        !self.grid.has_candidate(cell, digit) && self.grid.value(cell) != digit
+    }
+
+    fn chain_to(&self, cell: usize) -> Chain {
+        let mut cells = vec![cell];
+        while let Some(cause) = self.causes[cells[cells.len() - 1]] {
+            cells.push(cause);
+        }
+        cells.reverse();
+
+        let node = |cell: usize| Candidate::new(&self.grid, cell, self.grid.value(cell));
+        Chain {
+            start: node(cells[0]),
+            links: cells[1..]
+                .iter()
+                .map(|&cell| (Link::Implies, node(cell)))
+                .collect(),
+        }
+    }
+}
+
+pub(super) fn find_cell_forcing_chain(grid: &CandidateGrid) -> Option<Step> {
+    (0..grid.cell_count())
+        .filter(|&cell| (2..=3).contains(&grid.candidates(cell).count_ones()))
+        .find_map(|cell| {
+            let branches = digits(grid.candidates(cell))
+                .map(|digit| Branch::assume(grid, cell, digit))
+                .collect::<Vec<_>>();
+            forcing_step(grid, &branches, Technique::CellForcingChain, Vec::new())
+        })
+}
+
+pub(super) fn find_unit_forcing_chain(grid: &CandidateGrid) -> Option<Step> {
+    grid.units().find_map(|unit| {
+        grid.all_digits().find_map(|digit| {
+            let cells: Vec<usize> = grid
+                .unit_cells(unit)
+                .iter()
+                .copied()
+                .filter(|&cell| grid.has_candidate(cell, digit))
+                .collect();
+            if !(2..=3).contains(&cells.len()) {
+                return None;
+            }
+
+            let branches = cells
+                .iter()
+                .map(|&cell| Branch::assume(grid, cell, digit))
+                .collect::<Vec<_>>();
+            forcing_step(grid, &branches, Technique::UnitForcingChain, vec![unit])
+        })
+    })
+}
+
+// Whatever holds in every consistent branch holds in the puzzle.
+fn forcing_step(
+    grid: &CandidateGrid,
+    branches: &[Branch],
+    technique: Technique,
+    units: Vec<Unit>,
+) -> Option<Step> {
+    let live: Vec<&Branch> = branches
+        .iter()
+        .filter(|branch| !branch.contradiction)
+        .collect();
+    let first = live.first()?;
+
+    let placement = (0..grid.cell_count())
+        .filter(|&cell| grid.value(cell) == 0 && first.grid.value(cell) != 0)
+        .find(|&cell| {
+            live.iter()
+                .all(|branch| branch.grid.value(cell) == first.grid.value(cell))
+        });
+    if let Some(cell) = placement {
+        return Some(Step {
+            technique,
+            placements: vec![Candidate::new(grid, cell, first.grid.value(cell))],
+            eliminations: Vec::new(),
+            units,
+            chains: live.iter().map(|branch| branch.chain_to(cell)).collect(),
+        });
+    }
+
+    let elimination = (0..grid.cell_count()).find_map(|cell| {
+        digits(grid.candidates(cell))
+            .find(|&digit| live.iter().all(|branch| branch.excludes(cell, digit)))
+            .map(|digit| (cell, digit))
+    });
+    elimination.map(|(cell, digit)| Step {
+        technique,
+        placements: Vec::new(),
+        eliminations: vec![Candidate::new(grid, cell, digit)],
+        units,
+        chains: live
+            .iter()
+            .map(|branch| {
+                let source = if branch.grid.value(cell) != 0 {
+                    cell
+                } else {
+                    branch.removed_by[branch.index(cell, digit)].unwrap_or(cell)
+                };
+                branch.chain_to(source)
+            })
+            .collect(),
+    })
+}
+
+fn has_unit_contradiction(grid: &CandidateGrid) -> bool {
+    grid.units().any(|unit| {
+        let cells = grid.unit_cells(unit);
+        grid.all_digits().any(|digit| {
+            cells
+                .iter()
+                .all(|&cell| grid.value(cell) != digit && !grid.has_candidate(cell, digit))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::Sudoku;
+
+    fn grid_with_candidates(cells: &[(usize, &[u8])]) -> CandidateGrid {
+        let mut grid = CandidateGrid::from_sudoku(&Sudoku::from_string(&"0".repeat(81)).unwrap());
+        for &(cell, keep) in cells {
+            for digit in (1..=9).filter(|digit| !keep.contains(digit)) {
+                grid.eliminate(cell, digit);
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn test_find_cell_forcing_chain() {
+        let grid = grid_with_candidates(&[(40, &[1, 2]), (36, &[1, 3]), (4, &[2, 3])]);
+
+        let step = find_cell_forcing_chain(&grid).expect("Expected a cell forcing chain");
+        assert_eq!(step.eliminations, vec![Candidate::new(&grid, 0, 3)]);
+        let chains: Vec<String> = step.chains.iter().map(|chain| chain.to_string()).collect();
+        assert_eq!(chains, vec!["(2)r1c5 => (1)r5c5 => (3)r5c1", "(3)r1c5"]);
+    }
+
+    #[test]
+    fn test_find_unit_forcing_chain() {
+        let mut grid = grid_with_candidates(&[(0, &[1, 2]), (40, &[1, 2])]);
+        for cell in grid.unit_cells(Unit::Row(8)).to_vec() {
+            if cell != 72 && cell != 76 {
+                grid.eliminate(cell, 1);
+            }
+        }
+
+        let step = find_unit_forcing_chain(&grid).expect("Expected a unit forcing chain");
+        assert_eq!(step.units, vec![Unit::Row(8)]);
+        assert_eq!(step.eliminations, vec![Candidate::new(&grid, 4, 2)]);
+    }
+
+    #[test]
+    fn test_branch_detects_contradiction() {
+        let grid = grid_with_candidates(&[(0, &[1, 2]), (1, &[1, 2]), (2, &[1, 2])]);
+
+        let branch = Branch::assume(&grid, 0, 1);
+        assert!(branch.contradiction);
+    }
+}
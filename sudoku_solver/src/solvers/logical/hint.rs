@@ -0,0 +1,147 @@
+use std::fmt;
+
+use super::{Candidate, Chain, Step, Technique, Unit, describe};
+use crate::candidate_grid::CandidateGrid;
+use crate::{sudoku::Sudoku, validator::Validator};
+
+// The next logical deduction for a board, without solving any further.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    pub technique: Technique,
+    pub placement: Option<Candidate>,
+    pub eliminations: Vec<Candidate>,
+    pub units: Vec<Unit>,
+    pub chains: Vec<Chain>,
+}
+
+impl From<Step> for Hint {
+    fn from(step: Step) -> Self {
+        Self {
+            technique: step.technique,
+            placement: step.placements.first().copied(),
+            eliminations: step.eliminations,
+            units: step.units,
+            chains: step.chains,
+        }
+    }
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        describe(
+            f,
+            self.technique,
+            &self.units,
+            self.placement.as_slice(),
+            &self.eliminations,
+            &self.chains,
+        )
+    }
+}
+
+// Candidates are derived from the digits on the board, so an elimination hint
+// is relative to a freshly pencil-marked grid. Returns `None` for solved or
+// broken boards and for positions no technique on the ladder can crack.
+pub fn hint(board: &Sudoku) -> Option<Hint> {
+    if !Validator::is_valid_board(board) {
+        return None;
+    }
+
+    let grid = CandidateGrid::from_sudoku(board);
+    if grid.is_solved() || grid.has_contradiction() {
+        return None;
+    }
+
+    Technique::LADDER
+        .iter()
+        .find_map(|technique| technique.find(&grid))
+        .map(Hint::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::{Cell, Digit};
+    use crate::solvers::logical::LogicalSolver;
+
+    const FIENDISH: &str =
+        "000100597650009310000000004001003700060407000005800900030028000006000003070030001";
+
+    #[test]
+    fn test_hint_returns_single_placement() {
+        let input =
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+        let sudoku = Sudoku::from_string(input).unwrap();
+
+        let hint = hint(&sudoku).expect("Expected a hint");
+
+        assert_eq!(hint.technique, Technique::NakedSingle);
+        let placement = hint.placement.expect("Expected a placement");
+        assert_eq!(sudoku[placement.row][placement.col], 0);
+        assert!(hint.eliminations.is_empty());
+        assert_eq!(hint.units.len(), 3);
+        assert_eq!(
+            sudoku.to_string(),
+            input,
+            "Hint should not modify the board"
+        );
+    }
+
+    #[test]
+    fn test_hint_returns_eliminations_when_no_single_exists() {
+        let mut solved = Sudoku::from_string(FIENDISH).unwrap();
+        let steps = LogicalSolver::solve_with_steps(&mut solved).unwrap();
+        let mut sudoku = Sudoku::from_string(FIENDISH).unwrap();
+        for step in steps.iter().take_while(|step| !step.placements.is_empty()) {
+            for placement in &step.placements {
+                let cell = Cell::new(placement.row, placement.col).unwrap();
+                sudoku
+                    .set(cell, Digit::new(placement.digit).unwrap())
+                    .unwrap();
+            }
+        }
+
+        let hint = hint(&sudoku).expect("Expected a hint");
+
+        assert!(hint.placement.is_none(), "No single should be available");
+        assert!(!hint.eliminations.is_empty());
+        assert!(hint.technique > Technique::HiddenSingle);
+    }
+
+    #[test]
+    fn test_hint_on_solved_board() {
+        let sudoku = Sudoku::from_string(
+            "534678912672195348198342567859761423426853791713924856961537284287419635345286179",
+        )
+        .unwrap();
+
+        assert_eq!(hint(&sudoku), None);
+    }
+
+    #[test]
+    fn test_hint_on_invalid_board() {
+        let mut sudoku = Sudoku::from_string(FIENDISH).unwrap();
+        sudoku
+            .set(Cell::new(0, 0).unwrap(), Digit::new(1).unwrap())
+            .unwrap();
+
+        assert_eq!(hint(&sudoku), None);
+    }
+
+    #[test]
+    fn test_hint_display() {
+        let hint = Hint {
+            technique: Technique::HiddenSingle,
+            placement: Some(Candidate {
+                row: 0,
+                col: 2,
+                digit: 4,
+            }),
+            eliminations: Vec::new(),
+            units: vec![Unit::Box(0)],
+            chains: Vec::new(),
+        };
+
+        assert_eq!(hint.to_string(), "Hidden Single (box 1): r1c3=4");
+    }
+}
@@ -0,0 +1,130 @@
+use super::{Candidate, Step, Technique};
+use crate::candidate_grid::{CandidateGrid, Unit};
+
+pub(super) fn find_pointing(grid: &CandidateGrid) -> Option<Step> {
+    for b in 0..grid.size() {
+        let unit = Unit::Box(b);
+        for digit in grid.all_digits() {
+            let cells = cells_with_candidate(grid, unit, digit);
+            let Some(&first) = cells.first() else {
+                continue;
+            };
+
+            let (row, col) = (grid.row_of(first), grid.col_of(first));
+            let line = if cells.iter().all(|&cell| grid.row_of(cell) == row) {
+                Unit::Row(row)
+            } else if cells.iter().all(|&cell| grid.col_of(cell) == col) {
+                Unit::Column(col)
+            } else {
+                continue;
+            };
+
+            let eliminations =
+                eliminations_outside(grid, line, digit, |cell| grid.box_of(cell) == b);
+            if !eliminations.is_empty() {
+                return Some(Step {
+                    technique: Technique::PointingPair,
+                    placements: Vec::new(),
+                    eliminations,
+                    units: vec![unit, line],
+                    chains: Vec::new(),
+                });
+            }
+        }
+    }
+    None
+}
+
+pub(super) fn find_box_line_reduction(grid: &CandidateGrid) -> Option<Step> {
+    let size = grid.size();
+    for line in (0..size).map(Unit::Row).chain((0..size).map(Unit::Column)) {
+        for digit in grid.all_digits() {
+            let cells = cells_with_candidate(grid, line, digit);
+            let Some(&first) = cells.first() else {
+                continue;
+            };
+            if !cells
+                .iter()
+                .all(|&cell| grid.box_of(cell) == grid.box_of(first))
+            {
+                continue;
+            }
+
+            let unit = Unit::Box(grid.box_of(first));
+            let eliminations = eliminations_outside(grid, unit, digit, |cell| {
+                grid.unit_cells(line).contains(&cell)
+            });
+            if !eliminations.is_empty() {
+                return Some(Step {
+                    technique: Technique::BoxLineReduction,
+                    placements: Vec::new(),
+                    eliminations,
+                    units: vec![line, unit],
+                    chains: Vec::new(),
+                });
+            }
+        }
+    }
+    None
+}
+
+fn cells_with_candidate(grid: &CandidateGrid, unit: Unit, digit: u8) -> Vec<usize> {
+    grid.unit_cells(unit)
+        .iter()
+        .copied()
+        .filter(|&cell| grid.has_candidate(cell, digit))
+        .collect()
+}
+
+fn eliminations_outside<F>(
+    grid: &CandidateGrid,
+    unit: Unit,
+    digit: u8,
+    excluded: F,
+) -> Vec<Candidate>
+where
+    F: Fn(usize) -> bool,
+{
+    grid.unit_cells(unit)
+        .iter()
+        .copied()
+        .filter(|&cell| !excluded(cell) && grid.has_candidate(cell, digit))
+        .map(|cell| Candidate::new(grid, cell, digit))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::Sudoku;
+
+    #[test]
+    fn test_find_pointing() {
+        // Box 1 can only hold 1 in row 1, so 1 is removed from the rest of row 1.
+        let sudoku = Sudoku::from_string(
+            "000000000234000000567000000000000000000000000000000000000000000000000000000000000",
+        )
+        .unwrap();
+        let grid = CandidateGrid::from_sudoku(&sudoku);
+
+        let step = find_pointing(&grid).expect("Expected a pointing pair");
+        assert_eq!(step.units, vec![Unit::Box(0), Unit::Row(0)]);
+        assert!(step.eliminations.contains(&Candidate::new(&grid, 3, 1)));
+        assert!(step.eliminations.iter().all(|c| c.row == 0 && c.col > 2));
+    }
+
+    #[test]
+    fn test_find_box_line_reduction() {
+        // Row 1 can only hold 1 in box 1, so 1 is removed from the rest of box 1.
+        let sudoku = Sudoku::from_string(&"0".repeat(81)).unwrap();
+        let mut grid = CandidateGrid::from_sudoku(&sudoku);
+        for cell in 3..9 {
+            grid.eliminate(cell, 1);
+        }
+
+        let step = find_box_line_reduction(&grid).expect("Expected a box/line reduction");
+        assert_eq!(step.units, vec![Unit::Row(0), Unit::Box(0)]);
+        assert!(step.eliminations.contains(&Candidate::new(&grid, 9, 1)));
+        assert!(step.eliminations.iter().all(|c| c.row > 0 && c.col < 3));
+    }
+}
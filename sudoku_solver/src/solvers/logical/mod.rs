@@ -0,0 +1,542 @@
+mod cages;
+mod chains;
+mod coloring;
+mod fish;
+mod forcing;
+mod hint;
+mod intersections;
+mod rating;
+mod singles;
+mod subsets;
+mod wings;
+
+use std::fmt;
+
+use anyhow::Result;
+
+use super::{backtracking::BacktrackingSolver, solver::Solver};
+use crate::candidate_grid::CandidateGrid;
+use crate::sudoku::Sudoku;
+use crate::validator::Validator;
+
+pub use crate::candidate_grid::Unit;
+pub use hint::{Hint, hint};
+pub use rating::{Difficulty, Rating, rate};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Technique {
+    NakedSingle,
+    HiddenSingle,
+    CageCombination,
+    PointingPair,
+    BoxLineReduction,
+    NakedPair,
+    HiddenPair,
+    NakedTriple,
+    HiddenTriple,
+    XWing,
+    NakedQuad,
+    HiddenQuad,
+    Swordfish,
+    XYWing,
+    XYZWing,
+    WWing,
+    SimpleColoring,
+    Jellyfish,
+    FinnedXWing,
+    SashimiXWing,
+    FinnedSwordfish,
+    SashimiSwordfish,
+    FinnedJellyfish,
+    SashimiJellyfish,
+    XCycle,
+    XYChain,
+    AlternatingInferenceChain,
+    CellForcingChain,
+    UnitForcingChain,
+    Backtracking,
+}
+
+impl Technique {
+    // Techniques are tried in this order, easiest first.
+    pub const LADDER: [Technique; 29] = [
+        Technique::NakedSingle,
+        Technique::HiddenSingle,
+        Technique::CageCombination,
+        Technique::PointingPair,
+        Technique::BoxLineReduction,
+        Technique::NakedPair,
+        Technique::HiddenPair,
+        Technique::NakedTriple,
+        Technique::HiddenTriple,
+        Technique::XWing,
+        Technique::NakedQuad,
+        Technique::HiddenQuad,
+        Technique::Swordfish,
+        Technique::XYWing,
+        Technique::XYZWing,
+        Technique::WWing,
+        Technique::SimpleColoring,
+        Technique::Jellyfish,
+        Technique::FinnedXWing,
+        Technique::SashimiXWing,
+        Technique::FinnedSwordfish,
+        Technique::SashimiSwordfish,
+        Technique::FinnedJellyfish,
+        Technique::SashimiJellyfish,
+        Technique::XCycle,
+        Technique::XYChain,
+        Technique::AlternatingInferenceChain,
+        Technique::CellForcingChain,
+        Technique::UnitForcingChain,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Technique::NakedSingle => "Naked Single",
+            Technique::HiddenSingle => "Hidden Single",
+            Technique::CageCombination => "Cage Combination",
+            Technique::PointingPair => "Pointing Pair",
+            Technique::BoxLineReduction => "Box/Line Reduction",
+            Technique::NakedPair => "Naked Pair",
+            Technique::HiddenPair => "Hidden Pair",
+            Technique::NakedTriple => "Naked Triple",
+            Technique::HiddenTriple => "Hidden Triple",
+            Technique::XWing => "X-Wing",
+            Technique::NakedQuad => "Naked Quad",
+            Technique::HiddenQuad => "Hidden Quad",
+            Technique::Swordfish => "Swordfish",
+            Technique::XYWing => "XY-Wing",
+            Technique::XYZWing => "XYZ-Wing",
+            Technique::WWing => "W-Wing",
+            Technique::SimpleColoring => "Simple Coloring",
+            Technique::Jellyfish => "Jellyfish",
+            Technique::FinnedXWing => "Finned X-Wing",
+            Technique::SashimiXWing => "Sashimi X-Wing",
+            Technique::FinnedSwordfish => "Finned Swordfish",
+            Technique::SashimiSwordfish => "Sashimi Swordfish",
+            Technique::FinnedJellyfish => "Finned Jellyfish",
+            Technique::SashimiJellyfish => "Sashimi Jellyfish",
+            Technique::XCycle => "X-Cycle",
+            Technique::XYChain => "XY-Chain",
+            Technique::AlternatingInferenceChain => "Alternating Inference Chain",
+            Technique::CellForcingChain => "Cell Forcing Chain",
+            Technique::UnitForcingChain => "Unit Forcing Chain",
+            Technique::Backtracking => "Backtracking",
+        }
+    }
+
+    // Base score per use, on the same scale as HoDoKu.
+    pub fn score(self) -> u32 {
+        match self {
+            Technique::NakedSingle => 4,
+            Technique::HiddenSingle => 14,
+            Technique::CageCombination => 40,
+            Technique::PointingPair | Technique::BoxLineReduction => 50,
+            Technique::NakedPair => 60,
+            Technique::HiddenPair => 70,
+            Technique::NakedTriple => 80,
+            Technique::HiddenTriple => 100,
+            Technique::NakedQuad => 120,
+            Technique::FinnedXWing => 130,
+            Technique::XWing => 140,
+            Technique::HiddenQuad
+            | Technique::Swordfish
+            | Technique::WWing
+            | Technique::SimpleColoring
+            | Technique::SashimiXWing => 150,
+            Technique::XYWing | Technique::Jellyfish => 160,
+            Technique::XYZWing => 180,
+            Technique::FinnedSwordfish => 200,
+            Technique::SashimiSwordfish => 240,
+            Technique::FinnedJellyfish => 250,
+            Technique::SashimiJellyfish | Technique::XCycle | Technique::XYChain => 260,
+            Technique::AlternatingInferenceChain => 280,
+            Technique::CellForcingChain | Technique::UnitForcingChain => 500,
+            Technique::Backtracking => 10000,
+        }
+    }
+
+    fn find(self, grid: &CandidateGrid) -> Option<Step> {
+        match self {
+            Technique::NakedSingle => singles::find_naked_single(grid),
+            Technique::HiddenSingle => singles::find_hidden_single(grid),
+            Technique::CageCombination => cages::find_cage_combination(grid),
+            Technique::PointingPair => intersections::find_pointing(grid),
+            Technique::BoxLineReduction => intersections::find_box_line_reduction(grid),
+            Technique::NakedPair => subsets::find_naked_subset(grid, 2, self),
+            Technique::HiddenPair => subsets::find_hidden_subset(grid, 2, self),
+            Technique::NakedTriple => subsets::find_naked_subset(grid, 3, self),
+            Technique::HiddenTriple => subsets::find_hidden_subset(grid, 3, self),
+            Technique::XWing => fish::find_basic_fish(grid, 2, self),
+            Technique::NakedQuad => subsets::find_naked_subset(grid, 4, self),
+            Technique::HiddenQuad => subsets::find_hidden_subset(grid, 4, self),
+            Technique::Swordfish => fish::find_basic_fish(grid, 3, self),
+            Technique::XYWing => wings::find_xy_wing(grid),
+            Technique::XYZWing => wings::find_xyz_wing(grid),
+            Technique::WWing => wings::find_w_wing(grid),
+            Technique::SimpleColoring => coloring::find_simple_coloring(grid),
+            Technique::Jellyfish => fish::find_basic_fish(grid, 4, self),
+            Technique::FinnedXWing => fish::find_finned_fish(grid, 2, false, self),
+            Technique::SashimiXWing => fish::find_finned_fish(grid, 2, true, self),
+            Technique::FinnedSwordfish => fish::find_finned_fish(grid, 3, false, self),
+            Technique::SashimiSwordfish => fish::find_finned_fish(grid, 3, true, self),
+            Technique::FinnedJellyfish => fish::find_finned_fish(grid, 4, false, self),
+            Technique::SashimiJellyfish => fish::find_finned_fish(grid, 4, true, self),
+            Technique::XCycle => chains::find_x_cycle(grid),
+            Technique::XYChain => chains::find_xy_chain(grid),
+            Technique::AlternatingInferenceChain => chains::find_aic(grid),
+            Technique::CellForcingChain => forcing::find_cell_forcing_chain(grid),
+            Technique::UnitForcingChain => forcing::find_unit_forcing_chain(grid),
+            Technique::Backtracking => None,
+        }
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Candidate {
+    pub row: usize,
+    pub col: usize,
+    pub digit: u8,
+}
+
+impl Candidate {
+    fn new(grid: &CandidateGrid, cell: usize, digit: u8) -> Self {
+        Self {
+            row: grid.row_of(cell),
+            col: grid.col_of(cell),
+            digit,
+        }
+    }
+
+    fn cell(&self, grid: &CandidateGrid) -> usize {
+        grid.cell(self.row, self.col)
+    }
+}
+
+impl fmt::Display for Candidate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({})r{}c{}", self.digit, self.row + 1, self.col + 1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Link {
+    Strong,
+    Weak,
+    Implies,
+}
+
+impl fmt::Display for Link {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Link::Strong => write!(f, "="),
+            Link::Weak => write!(f, "-"),
+            Link::Implies => write!(f, " => "),
+        }
+    }
+}
+
+// A chain in Eureka notation, e.g. `(1)r1c2=(1)r1c5-(1)r3c5=(1)r3c9`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chain {
+    pub start: Candidate,
+    pub links: Vec<(Link, Candidate)>,
+}
+
+impl fmt::Display for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.start)?;
+        for (link, candidate) in &self.links {
+            write!(f, "{}{}", link, candidate)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub technique: Technique,
+    pub placements: Vec<Candidate>,
+    pub eliminations: Vec<Candidate>,
+    pub units: Vec<Unit>,
+    pub chains: Vec<Chain>,
+}
+
+impl Step {
+    fn apply(&self, grid: &mut CandidateGrid) {
+        for placement in &self.placements {
+            grid.place(placement.cell(grid), placement.digit);
+        }
+        for elimination in &self.eliminations {
+            grid.eliminate(elimination.cell(grid), elimination.digit);
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        describe(
+            f,
+            self.technique,
+            &self.units,
+            &self.placements,
+            &self.eliminations,
+            &self.chains,
+        )
+    }
+}
+
+fn describe(
+    f: &mut fmt::Formatter,
+    technique: Technique,
+    units: &[Unit],
+    placements: &[Candidate],
+    eliminations: &[Candidate],
+    chains: &[Chain],
+) -> fmt::Result {
+    write!(f, "{}", technique)?;
+    if !units.is_empty() {
+        let units: Vec<String> = units.iter().map(|unit| unit.to_string()).collect();
+        write!(f, " ({})", units.join(", "))?;
+    }
+
+    let effects: Vec<String> = placements
+        .iter()
+        .map(|c| format!("r{}c{}={}", c.row + 1, c.col + 1, c.digit))
+        .chain(
+            eliminations
+                .iter()
+                .map(|c| format!("r{}c{}<>{}", c.row + 1, c.col + 1, c.digit)),
+        )
+        .collect();
+    write!(f, ": {}", effects.join(", "))?;
+
+    if !chains.is_empty() {
+        let chains: Vec<String> = chains.iter().map(|chain| chain.to_string()).collect();
+        write!(f, " via {}", chains.join("; "))?;
+    }
+    Ok(())
+}
+
+pub struct LogicalSolver;
+
+impl LogicalSolver {
+    // The candidate grid only eliminates along units, so constraints such as
+    // cage sums are checked once the grid is full. The board is left as it
+    // was unless the result passes.
+    pub fn solve_with_steps(board: &mut Sudoku) -> Result<Vec<Step>> {
+        let mut grid = CandidateGrid::from_sudoku(board);
+        let steps = solve_grid(&mut grid)?;
+
+        let mut solution = board.clone();
+        for cell in 0..grid.cell_count() {
+            let (row, col) = (grid.row_of(cell), grid.col_of(cell));
+            if solution[row][col] == 0 {
+                solution.fill_cell(row, col, grid.value(cell));
+            }
+        }
+        if !Validator::is_valid_board(&solution) {
+            return Err(anyhow::anyhow!("Sudoku is unsolvable"));
+        }
+
+        *board = solution;
+        Ok(steps)
+    }
+}
+
+fn solve_grid(grid: &mut CandidateGrid) -> Result<Vec<Step>> {
+    let mut steps = Vec::new();
+
+    while !grid.is_solved() {
+        if grid.has_contradiction() {
+            return Err(anyhow::anyhow!("Unsolvable board"));
+        }
+
+        let step = match Technique::LADDER
+            .iter()
+            .find_map(|technique| technique.find(grid))
+        {
+            Some(step) => step,
+            None => search_remaining(grid)?,
+        };
+        step.apply(grid);
+        steps.push(step);
+    }
+
+    Ok(steps)
+}
+
+impl Solver for LogicalSolver {
+    fn solve(board: &mut Sudoku) -> Result<(), anyhow::Error> {
+        Self::solve_with_steps(board).map(|_| ())
+    }
+}
+
+fn search_remaining(grid: &CandidateGrid) -> Result<Step> {
+    let mut board = grid.to_sudoku()?;
+    BacktrackingSolver::solve(&mut board)?;
+
+    let placements = (0..grid.cell_count())
+        .filter(|&cell| grid.value(cell) == 0)
+        .map(|cell| Candidate::new(grid, cell, board[grid.row_of(cell)][grid.col_of(cell)]))
+        .collect();
+
+    Ok(Step {
+        technique: Technique::Backtracking,
+        placements,
+        eliminations: Vec::new(),
+        units: Vec::new(),
+        chains: Vec::new(),
+    })
+}
+
+fn combinations(items: &[usize], size: usize) -> Vec<Vec<usize>> {
+    if size == 0 {
+        return vec![Vec::new()];
+    }
+    if items.len() < size {
+        return Vec::new();
+    }
+
+    let mut result = Vec::new();
+    for (i, &first) in items.iter().enumerate() {
+        for mut rest in combinations(&items[i + 1..], size - 1) {
+            rest.insert(0, first);
+            result.push(rest);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logical_solver_solves_easy_sudoku_without_search() {
+        let input =
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+        let expected_output =
+            "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+        let mut sudoku = Sudoku::from_string(input).unwrap();
+
+        let steps = LogicalSolver::solve_with_steps(&mut sudoku).unwrap();
+
+        assert_eq!(sudoku.to_string(), expected_output);
+        assert!(
+            steps
+                .iter()
+                .all(|step| step.technique != Technique::Backtracking),
+            "Easy sudoku should not need search"
+        );
+    }
+
+    #[test]
+    fn test_logical_solver_records_one_placement_per_empty_cell() {
+        let input =
+            "000000000603140500902500807520090614300000000001005209730800000009000006060010070";
+        let mut sudoku = Sudoku::from_string(input).unwrap();
+        let empty_cells = input.chars().filter(|&c| c == '0').count();
+
+        let steps = LogicalSolver::solve_with_steps(&mut sudoku).unwrap();
+
+        let placements: usize = steps.iter().map(|step| step.placements.len()).sum();
+        assert_eq!(placements, empty_cells);
+        assert_eq!(
+            sudoku.to_string(),
+            "475289163683147592912563847527398614396421758841675239734856921159732486268914375"
+        );
+    }
+
+    #[test]
+    fn test_logical_solver_solves_fiendish_sudoku_with_wings_and_coloring() {
+        let input =
+            "000100597650009310000000004001003700060407000005800900030028000006000003070030001";
+        let mut sudoku = Sudoku::from_string(input).unwrap();
+
+        let steps = LogicalSolver::solve_with_steps(&mut sudoku).unwrap();
+
+        assert_eq!(
+            sudoku.to_string(),
+            "423186597657249318918375264891563742362497185745812936134728659286951473579634821"
+        );
+        assert!(steps.iter().any(|step| step.technique == Technique::XYWing));
+        assert!(
+            steps
+                .iter()
+                .any(|step| step.technique == Technique::SimpleColoring)
+        );
+        assert!(
+            steps
+                .iter()
+                .all(|step| step.technique != Technique::Backtracking)
+        );
+    }
+
+    #[test]
+    fn test_logical_solver_falls_back_to_search_on_empty_board() {
+        let mut sudoku = Sudoku::from_string(&"0".repeat(81)).unwrap();
+
+        let steps = LogicalSolver::solve_with_steps(&mut sudoku).unwrap();
+
+        assert_eq!(
+            steps.last().map(|step| step.technique),
+            Some(Technique::Backtracking)
+        );
+        assert!(!sudoku.to_string().contains('0'));
+    }
+
+    #[test]
+    fn test_step_display() {
+        let grid = CandidateGrid::from_sudoku(&Sudoku::from_string(&"0".repeat(81)).unwrap());
+        let step = Step {
+            technique: Technique::NakedPair,
+            placements: Vec::new(),
+            eliminations: vec![Candidate::new(&grid, 3, 4), Candidate::new(&grid, 5, 4)],
+            units: vec![Unit::Row(0)],
+            chains: Vec::new(),
+        };
+
+        assert_eq!(step.to_string(), "Naked Pair (row 1): r1c4<>4, r1c6<>4");
+    }
+
+    #[test]
+    fn test_step_display_with_chain() {
+        let grid = CandidateGrid::from_sudoku(&Sudoku::from_string(&"0".repeat(81)).unwrap());
+        let step = Step {
+            technique: Technique::XYChain,
+            placements: Vec::new(),
+            eliminations: vec![Candidate::new(&grid, 8, 1)],
+            units: Vec::new(),
+            chains: vec![Chain {
+                start: Candidate::new(&grid, 0, 1),
+                links: vec![
+                    (Link::Strong, Candidate::new(&grid, 0, 2)),
+                    (Link::Weak, Candidate::new(&grid, 4, 2)),
+                    (Link::Strong, Candidate::new(&grid, 4, 1)),
+                ],
+            }],
+        };
+
+        assert_eq!(
+            step.to_string(),
+            "XY-Chain: r1c9<>1 via (1)r1c1=(2)r1c1-(2)r1c5=(1)r1c5"
+        );
+    }
+
+    #[test]
+    fn test_combinations() {
+        assert_eq!(
+            combinations(&[1, 2, 3], 2),
+            vec![vec![1, 2], vec![1, 3], vec![2, 3]]
+        );
+        assert!(combinations(&[1], 2).is_empty());
+    }
+}
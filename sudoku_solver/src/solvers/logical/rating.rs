@@ -0,0 +1,208 @@
+use std::fmt;
+
+use anyhow::Result;
+
+use super::{Technique, solve_grid};
+use crate::candidate_grid::CandidateGrid;
+use crate::{sudoku::Sudoku, validator::Validator};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Fiendish,
+    Extreme,
+}
+
+impl Difficulty {
+    pub fn of(technique: Technique) -> Self {
+        match technique {
+            Technique::NakedSingle | Technique::HiddenSingle => Difficulty::Easy,
+            Technique::PointingPair
+            | Technique::BoxLineReduction
+            | Technique::NakedPair
+            | Technique::HiddenPair
+            | Technique::NakedTriple
+            | Technique::HiddenTriple
+            | Technique::CageCombination => Difficulty::Medium,
+            Technique::XWing
+            | Technique::NakedQuad
+            | Technique::HiddenQuad
+            | Technique::Swordfish
+            | Technique::XYWing
+            | Technique::XYZWing
+            | Technique::WWing
+            | Technique::SimpleColoring
+            | Technique::Jellyfish => Difficulty::Hard,
+            Technique::FinnedXWing
+            | Technique::SashimiXWing
+            | Technique::FinnedSwordfish
+            | Technique::SashimiSwordfish
+            | Technique::FinnedJellyfish
+            | Technique::SashimiJellyfish
+            | Technique::XCycle
+            | Technique::XYChain
+            | Technique::AlternatingInferenceChain => Difficulty::Fiendish,
+            Technique::CellForcingChain | Technique::UnitForcingChain | Technique::Backtracking => {
+                Difficulty::Extreme
+            }
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+            Difficulty::Fiendish => "Fiendish",
+            Difficulty::Extreme => "Extreme",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rating {
+    pub difficulty: Difficulty,
+    pub hardest: Technique,
+    pub score: u32,
+    // Uses of each technique, in ladder order.
+    pub counts: Vec<(Technique, usize)>,
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} (score {}, hardest: {})",
+            self.difficulty, self.score, self.hardest
+        )?;
+        for (technique, count) in &self.counts {
+            write!(f, "\n  {:<28} {}", technique.name(), count)?;
+        }
+        Ok(())
+    }
+}
+
+// Solves easiest-first and grades by the hardest technique needed; the score
+// adds up the base score of every step taken.
+pub fn rate(board: &Sudoku) -> Result<Rating> {
+    if !Validator::is_valid_board(board) {
+        return Err(anyhow::anyhow!("Invalid board"));
+    }
+
+    let mut grid = CandidateGrid::from_sudoku(board);
+    let steps = solve_grid(&mut grid)?;
+
+    let mut counts: Vec<(Technique, usize)> = Vec::new();
+    for step in &steps {
+        match counts
+            .iter_mut()
+            .find(|(technique, _)| *technique == step.technique)
+        {
+            Some((_, count)) => *count += 1,
+            None => counts.push((step.technique, 1)),
+        }
+    }
+    counts.sort();
+
+    let hardest = counts
+        .last()
+        .map_or(Technique::NakedSingle, |&(technique, _)| technique);
+    let score = counts
+        .iter()
+        .map(|&(technique, count)| technique.score() * count as u32)
+        .sum();
+
+    Ok(Rating {
+        difficulty: Difficulty::of(hardest),
+        hardest,
+        score,
+        counts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cell::{Cell, Digit};
+
+    const EASY: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+    const MEDIUM: &str =
+        "300200000000107000706030500070009080900020004010800050009040301000702000000008006";
+    const HARD: &str =
+        "000000000603140500902500807520090614300000000001005209730800000009000006060010070";
+    const FIENDISH: &str =
+        "000704005020010070000080002090006250600070008053200010400090000030060090200407000";
+
+    fn rate_str(input: &str) -> Rating {
+        rate(&Sudoku::from_string(input).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_rate_easy_sudoku() {
+        let rating = rate_str(EASY);
+
+        assert_eq!(rating.difficulty, Difficulty::Easy);
+        assert!(rating.hardest <= Technique::HiddenSingle);
+        let placements: usize = rating.counts.iter().map(|(_, count)| count).sum();
+        assert_eq!(placements, EASY.chars().filter(|&c| c == '0').count());
+    }
+
+    #[test]
+    fn test_rate_medium_sudoku() {
+        let rating = rate_str(MEDIUM);
+
+        assert_eq!(rating.difficulty, Difficulty::Medium);
+        assert_eq!(rating.hardest, Technique::HiddenPair);
+    }
+
+    #[test]
+    fn test_rate_hard_sudoku() {
+        let rating = rate_str(HARD);
+
+        assert_eq!(rating.difficulty, Difficulty::Hard);
+        assert_eq!(rating.hardest, Technique::XYWing);
+        assert!(rating.counts.contains(&(Technique::NakedPair, 2)));
+    }
+
+    #[test]
+    fn test_rate_fiendish_sudoku() {
+        let rating = rate_str(FIENDISH);
+
+        assert_eq!(rating.difficulty, Difficulty::Fiendish);
+        assert_eq!(rating.hardest, Technique::XYChain);
+    }
+
+    #[test]
+    fn test_ratings_are_ordered_by_difficulty() {
+        let ratings: Vec<Rating> = [EASY, MEDIUM, HARD, FIENDISH].map(rate_str).into();
+
+        for pair in ratings.windows(2) {
+            assert!(pair[0].difficulty < pair[1].difficulty);
+            assert!(pair[0].score < pair[1].score);
+        }
+    }
+
+    #[test]
+    fn test_rate_empty_board_needs_search() {
+        let rating = rate_str(&"0".repeat(81));
+
+        assert_eq!(rating.hardest, Technique::Backtracking);
+        assert_eq!(rating.difficulty, Difficulty::Extreme);
+    }
+
+    #[test]
+    fn test_rate_rejects_invalid_board() {
+        let mut sudoku = Sudoku::from_string(FIENDISH).unwrap();
+        sudoku
+            .set(Cell::new(0, 0).unwrap(), Digit::new(7).unwrap())
+            .unwrap();
+
+        assert!(rate(&sudoku).is_err());
+    }
+}
@@ -0,0 +1,70 @@
+use super::{Candidate, Step, Technique};
+use crate::candidate_grid::CandidateGrid;
+
+pub(super) fn find_naked_single(grid: &CandidateGrid) -> Option<Step> {
+    (0..grid.cell_count()).find_map(|cell| {
+        let mask = grid.candidates(cell);
+        (mask.count_ones() == 1).then(|| Step {
+            technique: Technique::NakedSingle,
+            placements: vec![Candidate::new(grid, cell, mask.trailing_zeros() as u8)],
+            eliminations: Vec::new(),
+            units: grid.units_of(cell).collect(),
+            chains: Vec::new(),
+        })
+    })
+}
+
+pub(super) fn find_hidden_single(grid: &CandidateGrid) -> Option<Step> {
+    for unit in grid.units() {
+        for digit in grid.all_digits() {
+            let mut cells = grid
+                .unit_cells(unit)
+                .iter()
+                .copied()
+                .filter(|&cell| grid.has_candidate(cell, digit));
+
+            if let (Some(cell), None) = (cells.next(), cells.next()) {
+                return Some(Step {
+                    technique: Technique::HiddenSingle,
+                    placements: vec![Candidate::new(grid, cell, digit)],
+                    eliminations: Vec::new(),
+                    units: vec![unit],
+                    chains: Vec::new(),
+                });
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{candidate_grid::Unit, sudoku::Sudoku};
+
+    #[test]
+    fn test_find_naked_single() {
+        let sudoku = Sudoku::from_string(
+            "123456780000000000000000000000000000000000000000000000000000000000000000000000000",
+        )
+        .unwrap();
+        let grid = CandidateGrid::from_sudoku(&sudoku);
+
+        let step = find_naked_single(&grid).expect("Expected a naked single");
+        assert_eq!(step.placements, vec![Candidate::new(&grid, 8, 9)]);
+    }
+
+    #[test]
+    fn test_find_hidden_single() {
+        // The 1s in rows 2 and 3 and columns 2 and 3 leave r1c1 as the only place for 1 in box 1.
+        let sudoku = Sudoku::from_string(
+            "000000000000100000000000100010000000000000000000000000001000000000000000000000000",
+        )
+        .unwrap();
+        let grid = CandidateGrid::from_sudoku(&sudoku);
+
+        let step = find_hidden_single(&grid).expect("Expected a hidden single");
+        assert_eq!(step.placements, vec![Candidate::new(&grid, 0, 1)]);
+        assert_eq!(step.units, vec![Unit::Box(0)]);
+    }
+}
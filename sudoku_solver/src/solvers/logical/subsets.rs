@@ -0,0 +1,149 @@
+use super::{Candidate, Step, Technique, combinations};
+use crate::candidate_grid::{CandidateGrid, Unit, digits};
+
+pub(super) fn find_naked_subset(
+    grid: &CandidateGrid,
+    size: usize,
+    technique: Technique,
+) -> Option<Step> {
+    for unit in grid.units() {
+        let empty = empty_cells(grid, unit);
+        if empty.len() <= size {
+            continue;
+        }
+
+        for subset in combinations(&empty, size) {
+            let mask = subset
+                .iter()
+                .fold(0, |mask, &cell| mask | grid.candidates(cell));
+            if mask.count_ones() as usize != size {
+                continue;
+            }
+
+            let eliminations: Vec<Candidate> = empty
+                .iter()
+                .filter(|cell| !subset.contains(cell))
+                .flat_map(|&cell| {
+                    digits(grid.candidates(cell) & mask)
+                        .map(move |digit| Candidate::new(grid, cell, digit))
+                })
+                .collect();
+
+            if !eliminations.is_empty() {
+                return Some(Step {
+                    technique,
+                    placements: Vec::new(),
+                    eliminations,
+                    units: vec![unit],
+                    chains: Vec::new(),
+                });
+            }
+        }
+    }
+    None
+}
+
+pub(super) fn find_hidden_subset(
+    grid: &CandidateGrid,
+    size: usize,
+    technique: Technique,
+) -> Option<Step> {
+    for unit in grid.units() {
+        let empty = empty_cells(grid, unit);
+        let unplaced: Vec<usize> = (1..=grid.size())
+            .filter(|&digit| {
+                empty
+                    .iter()
+                    .any(|&cell| grid.has_candidate(cell, digit as u8))
+            })
+            .collect();
+        if unplaced.len() <= size {
+            continue;
+        }
+
+        for subset in combinations(&unplaced, size) {
+            let mask = subset.iter().fold(0u32, |mask, &digit| mask | 1 << digit);
+            let cells: Vec<usize> = empty
+                .iter()
+                .copied()
+                .filter(|&cell| grid.candidates(cell) & mask != 0)
+                .collect();
+            if cells.len() != size {
+                continue;
+            }
+
+            let eliminations: Vec<Candidate> = cells
+                .iter()
+                .flat_map(|&cell| {
+                    digits(grid.candidates(cell) & !mask)
+                        .map(move |digit| Candidate::new(grid, cell, digit))
+                })
+                .collect();
+
+            if !eliminations.is_empty() {
+                return Some(Step {
+                    technique,
+                    placements: Vec::new(),
+                    eliminations,
+                    units: vec![unit],
+                    chains: Vec::new(),
+                });
+            }
+        }
+    }
+    None
+}
+
+fn empty_cells(grid: &CandidateGrid, unit: Unit) -> Vec<usize> {
+    grid.unit_cells(unit)
+        .iter()
+        .copied()
+        .filter(|&cell| grid.value(cell) == 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::Sudoku;
+
+    fn empty_grid() -> CandidateGrid {
+        CandidateGrid::from_sudoku(&Sudoku::from_string(&"0".repeat(81)).unwrap())
+    }
+
+    #[test]
+    fn test_find_naked_pair() {
+        let mut grid = empty_grid();
+        for cell in [0, 1] {
+            for digit in 3..=9 {
+                grid.eliminate(cell, digit);
+            }
+        }
+
+        let step =
+            find_naked_subset(&grid, 2, Technique::NakedPair).expect("Expected a naked pair");
+        assert_eq!(step.units, vec![Unit::Box(0)]);
+        assert!(step.eliminations.contains(&Candidate::new(&grid, 2, 1)));
+        assert!(step.eliminations.contains(&Candidate::new(&grid, 20, 2)));
+        assert!(
+            step.eliminations
+                .iter()
+                .all(|c| c.digit <= 2 && c.cell(&grid) != 0 && c.cell(&grid) != 1)
+        );
+    }
+
+    #[test]
+    fn test_find_hidden_pair() {
+        let mut grid = empty_grid();
+        for cell in grid.unit_cells(Unit::Row(4)).to_vec().into_iter().skip(2) {
+            grid.eliminate(cell, 1);
+            grid.eliminate(cell, 2);
+        }
+
+        let step =
+            find_hidden_subset(&grid, 2, Technique::HiddenPair).expect("Expected a hidden pair");
+        assert_eq!(step.units, vec![Unit::Row(4)]);
+        assert_eq!(step.eliminations.len(), 14);
+        assert!(step.eliminations.iter().all(|c| c.digit > 2 && c.row == 4));
+    }
+}
@@ -0,0 +1,195 @@
+use super::{Candidate, Step, Technique};
+use crate::candidate_grid::{CandidateGrid, Unit, digits};
+
+pub(super) fn find_xy_wing(grid: &CandidateGrid) -> Option<Step> {
+    for pivot in bivalue_cells(grid) {
+        let pivot_mask = grid.candidates(pivot);
+        let pincers: Vec<usize> = grid
+            .peers(pivot)
+            .iter()
+            .copied()
+            .filter(|&cell| grid.candidates(cell).count_ones() == 2)
+            .collect();
+
+        for (i, &first) in pincers.iter().enumerate() {
+            for &second in &pincers[i + 1..] {
+                let (a, b) = (grid.candidates(first), grid.candidates(second));
+                let z = a & b & !pivot_mask;
+                if z.count_ones() != 1
+                    || (a & pivot_mask).count_ones() != 1
+                    || (b & pivot_mask).count_ones() != 1
+                    || a & pivot_mask == b & pivot_mask
+                {
+                    continue;
+                }
+
+                let step = wing_step(grid, Technique::XYWing, &[first, second], z, Vec::new());
+                if step.is_some() {
+                    return step;
+                }
+            }
+        }
+    }
+    None
+}
+
+pub(super) fn find_xyz_wing(grid: &CandidateGrid) -> Option<Step> {
+    for pivot in (0..grid.cell_count()).filter(|&cell| grid.candidates(cell).count_ones() == 3) {
+        let pivot_mask = grid.candidates(pivot);
+        let pincers: Vec<usize> = grid
+            .peers(pivot)
+            .iter()
+            .copied()
+            .filter(|&cell| {
+                let mask = grid.candidates(cell);
+                mask.count_ones() == 2 && mask & !pivot_mask == 0
+            })
+            .collect();
+
+        for (i, &first) in pincers.iter().enumerate() {
+            for &second in &pincers[i + 1..] {
+                let (a, b) = (grid.candidates(first), grid.candidates(second));
+                let z = a & b;
+                if a == b || z.count_ones() != 1 {
+                    continue;
+                }
+
+                let step = wing_step(
+                    grid,
+                    Technique::XYZWing,
+                    &[pivot, first, second],
+                    z,
+                    Vec::new(),
+                );
+                if step.is_some() {
+                    return step;
+                }
+            }
+        }
+    }
+    None
+}
+
+pub(super) fn find_w_wing(grid: &CandidateGrid) -> Option<Step> {
+    let cells = bivalue_cells(grid);
+
+    for (i, &first) in cells.iter().enumerate() {
+        for &second in &cells[i + 1..] {
+            let mask = grid.candidates(first);
+            if grid.candidates(second) != mask || grid.sees(first, second) {
+                continue;
+            }
+
+            for x in digits(mask) {
+                let y = mask & !(1 << x);
+                for unit in grid.units() {
+                    let link: Vec<usize> = grid
+                        .unit_cells(unit)
+                        .iter()
+                        .copied()
+                        .filter(|&cell| grid.has_candidate(cell, x))
+                        .collect();
+                    let [p, q] = link[..] else {
+                        continue;
+                    };
+                    if [p, q].iter().any(|cell| *cell == first || *cell == second) {
+                        continue;
+                    }
+
+                    let linked = (grid.sees(p, first) && grid.sees(q, second))
+                        || (grid.sees(p, second) && grid.sees(q, first));
+                    if !linked {
+                        continue;
+                    }
+
+                    let step = wing_step(grid, Technique::WWing, &[first, second], y, vec![unit]);
+                    if step.is_some() {
+                        return step;
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+fn bivalue_cells(grid: &CandidateGrid) -> Vec<usize> {
+    (0..grid.cell_count())
+        .filter(|&cell| grid.candidates(cell).count_ones() == 2)
+        .collect()
+}
+
+fn wing_step(
+    grid: &CandidateGrid,
+    technique: Technique,
+    wing: &[usize],
+    mask: u32,
+    units: Vec<Unit>,
+) -> Option<Step> {
+    let digit = mask.trailing_zeros() as u8;
+    let eliminations: Vec<Candidate> = (0..grid.cell_count())
+        .filter(|cell| !wing.contains(cell) && grid.has_candidate(*cell, digit))
+        .filter(|&cell| wing.iter().all(|&other| grid.sees(cell, other)))
+        .map(|cell| Candidate::new(grid, cell, digit))
+        .collect();
+
+    (!eliminations.is_empty()).then_some(Step {
+        technique,
+        placements: Vec::new(),
+        eliminations,
+        units,
+        chains: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sudoku::Sudoku;
+
+    fn grid_with_candidates(cells: &[(usize, &[u8])]) -> CandidateGrid {
+        let mut grid = CandidateGrid::from_sudoku(&Sudoku::from_string(&"0".repeat(81)).unwrap());
+        for &(cell, keep) in cells {
+            for digit in (1..=9).filter(|digit| !keep.contains(digit)) {
+                grid.eliminate(cell, digit);
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn test_find_xy_wing() {
+        let grid = grid_with_candidates(&[(40, &[1, 2]), (36, &[1, 3]), (4, &[2, 3])]);
+
+        let step = find_xy_wing(&grid).expect("Expected an XY-Wing");
+        assert_eq!(step.eliminations, vec![Candidate::new(&grid, 0, 3)]);
+    }
+
+    #[test]
+    fn test_find_xyz_wing() {
+        let grid = grid_with_candidates(&[(40, &[1, 2, 3]), (39, &[1, 3]), (4, &[2, 3])]);
+
+        let step = find_xyz_wing(&grid).expect("Expected an XYZ-Wing");
+        assert_eq!(
+            step.eliminations,
+            vec![Candidate::new(&grid, 31, 3), Candidate::new(&grid, 49, 3)]
+        );
+    }
+
+    #[test]
+    fn test_find_w_wing() {
+        let mut grid = grid_with_candidates(&[(0, &[1, 2]), (40, &[1, 2])]);
+        for cell in grid.unit_cells(Unit::Row(8)).to_vec() {
+            if cell != 72 && cell != 76 {
+                grid.eliminate(cell, 1);
+            }
+        }
+
+        let step = find_w_wing(&grid).expect("Expected a W-Wing");
+        assert_eq!(step.units, vec![Unit::Row(8)]);
+        assert_eq!(
+            step.eliminations,
+            vec![Candidate::new(&grid, 4, 2), Candidate::new(&grid, 36, 2)]
+        );
+    }
+}
@@ -1,3 +1,4 @@
 pub mod backtracking;
 pub mod heuristic;
+pub mod logical;
 pub mod solver;
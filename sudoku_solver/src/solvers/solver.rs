@@ -1,26 +1,59 @@
 use crate::sudoku::Sudoku;
 
-use super::{backtracking::BacktrackingSolver, heuristic::Heuristic};
+use super::{backtracking::BacktrackingSolver, heuristic::Heuristic, logical::LogicalSolver};
 
 pub trait Solver {
     fn solve(board: &mut Sudoku) -> Result<(), anyhow::Error>;
 }
 
+#[cfg_attr(feature = "wasm-bindgen", wasm_bindgen::prelude::wasm_bindgen)]
+#[derive(Debug, Clone, Copy)]
 pub enum SolverKind {
     Backtracking,
     Heuristic,
+    Logical,
 }
 
 pub fn solve_with_strategy(board: &mut Sudoku, kind: SolverKind) -> anyhow::Result<()> {
     match kind {
         SolverKind::Backtracking => BacktrackingSolver::solve(board),
         SolverKind::Heuristic => Heuristic::solve(board),
+        SolverKind::Logical => LogicalSolver::solve(board),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::{Duration, Instant};
+
+    use crate::constraint::Constraint;
+    use crate::regions::Regions;
+    use crate::validator::Validator;
+
+    // Solves `board` with every strategy. `expected` is the solution, or
+    // `None` when each strategy should fail and leave the board as it was.
+    fn assert_every_strategy(board: &Sudoku, expected: Option<&str>) {
+        for kind in [
+            SolverKind::Backtracking,
+            SolverKind::Heuristic,
+            SolverKind::Logical,
+        ] {
+            let mut sudoku = board.clone();
+            let result = solve_with_strategy(&mut sudoku, kind);
+            match expected {
+                Some(solution) => {
+                    assert!(result.is_ok(), "{:?}: {:?}", kind, result);
+                    assert_eq!(sudoku.to_string(), solution, "{:?}", kind);
+                    assert!(Validator::is_valid_board(&sudoku), "{:?}", kind);
+                }
+                None => {
+                    assert!(result.is_err(), "{:?} solved {}", kind, sudoku);
+                    assert_eq!(sudoku.to_string(), board.to_string(), "{:?}", kind);
+                }
+            }
+        }
+    }
 
     #[test]
     fn test_solve_using_backtracking() {
@@ -118,6 +151,38 @@ mod tests {
         assert_eq!(sudoku, expected_solution);
     }
 
+    #[test]
+    fn test_solve_using_logical_strategy() {
+        let mut sudoku = Sudoku::new(vec![
+            vec![5, 3, 0, 0, 7, 0, 0, 0, 0],
+            vec![6, 0, 0, 1, 9, 5, 0, 0, 0],
+            vec![0, 9, 8, 0, 0, 0, 0, 6, 0],
+            vec![8, 0, 0, 0, 6, 0, 0, 0, 3],
+            vec![4, 0, 0, 8, 0, 3, 0, 0, 1],
+            vec![7, 0, 0, 0, 2, 0, 0, 0, 6],
+            vec![0, 6, 0, 0, 0, 0, 2, 8, 0],
+            vec![0, 0, 0, 4, 1, 9, 0, 0, 5],
+            vec![0, 0, 0, 0, 8, 0, 0, 7, 9],
+        ])
+        .unwrap();
+
+        let expected_solution = Sudoku::new(vec![
+            vec![5, 3, 4, 6, 7, 8, 9, 1, 2],
+            vec![6, 7, 2, 1, 9, 5, 3, 4, 8],
+            vec![1, 9, 8, 3, 4, 2, 5, 6, 7],
+            vec![8, 5, 9, 7, 6, 1, 4, 2, 3],
+            vec![4, 2, 6, 8, 5, 3, 7, 9, 1],
+            vec![7, 1, 3, 9, 2, 4, 8, 5, 6],
+            vec![9, 6, 1, 5, 3, 7, 2, 8, 4],
+            vec![2, 8, 7, 4, 1, 9, 6, 3, 5],
+            vec![3, 4, 5, 2, 8, 6, 1, 7, 9],
+        ])
+        .unwrap();
+
+        assert!(solve_with_strategy(&mut sudoku, SolverKind::Logical).is_ok());
+        assert_eq!(sudoku, expected_solution);
+    }
+
     #[test]
     fn test_empty_board_with_backtracking() {
         let empty_board = vec![
@@ -138,4 +203,297 @@ mod tests {
             "Empty board should be solvable!"
         );
     }
+
+    #[test]
+    fn test_solve_six_by_six_with_every_strategy() {
+        let sudoku = Sudoku::from_string("1.3.5..5.1.32..5.4.6..3.3.2.4..4.3.2").unwrap();
+
+        assert_every_strategy(&sudoku, Some("123456456123231564564231312645645312"));
+    }
+
+    #[test]
+    fn test_unsolvable_and_invalid_boards_with_every_strategy() {
+        // r1c1 has no digit left, though nothing clashes yet.
+        let stuck = Sudoku::from_string("0201340000000000").unwrap();
+        assert_every_strategy(&stuck, None);
+
+        let mut clashing = Sudoku::from_string("1000000000000000").unwrap();
+        clashing.fill_cell(0, 3, 1);
+        assert_every_strategy(&clashing, None);
+    }
+
+    #[test]
+    fn test_empty_four_by_four_board() {
+        let mut sudoku = Sudoku::from_string(&"0".repeat(16)).unwrap();
+
+        assert!(Heuristic::solve(&mut sudoku).is_ok());
+        assert!(!sudoku.to_string().contains('0'), "Board should be filled");
+    }
+
+    #[test]
+    fn test_heuristic_empties_cells_when_backtracking() {
+        let mut sudoku = Sudoku::from_string(
+            "800000000003600000070090200050007000000045700000100030001000068008500010090000400",
+        )
+        .unwrap();
+        let expected_solution = Sudoku::from_string(
+            "812753649943682175675491283154237896369845721287169534521974368438526917796318452",
+        )
+        .unwrap();
+
+        assert!(Heuristic::solve(&mut sudoku).is_ok());
+        assert_eq!(sudoku, expected_solution, "Needs deep backtracking");
+    }
+
+    #[test]
+    fn test_solve_x_sudoku_with_every_strategy() {
+        let puzzle =
+            "002090000040001000000406200710040025500007800600002071000100008900000002068200000";
+        let sudoku = Sudoku::from_string(puzzle)
+            .unwrap()
+            .with_constraints(vec![Constraint::Diagonals])
+            .unwrap();
+
+        // Diagonals make the solution unique.
+        assert_every_strategy(
+            &sudoku,
+            Some(
+                "872593146346821759195476283719348625523617894684952371257164938931785462468239517",
+            ),
+        );
+    }
+
+    #[test]
+    fn test_solve_jigsaw_with_every_strategy() {
+        let regions = Regions::new(&[
+            vec![0, 0, 0, 1, 1, 1, 2, 2, 2],
+            vec![0, 0, 3, 1, 1, 1, 2, 5, 5],
+            vec![0, 3, 3, 1, 1, 1, 2, 2, 5],
+            vec![0, 3, 3, 3, 3, 4, 4, 2, 5],
+            vec![0, 3, 3, 4, 4, 4, 2, 2, 5],
+            vec![0, 4, 4, 4, 4, 5, 5, 5, 5],
+            vec![7, 7, 7, 7, 8, 8, 8, 8, 8],
+            vec![6, 6, 6, 7, 7, 7, 7, 7, 8],
+            vec![6, 6, 6, 6, 6, 6, 8, 8, 8],
+        ])
+        .unwrap();
+        let puzzle =
+            "030001700007089030806700590000500067000000000200300654040820000000910003060073400";
+        let board = puzzle
+            .as_bytes()
+            .chunks(9)
+            .map(|row| row.iter().map(|byte| byte - b'0').collect())
+            .collect();
+        let sudoku = Sudoku::jigsaw(board, regions).unwrap();
+
+        assert_every_strategy(
+            &sudoku,
+            Some(
+                "639251748457689132826734591193542867718465329271398654345827916584916273962173485",
+            ),
+        );
+    }
+
+    #[test]
+    fn test_solve_killer_with_every_strategy() {
+        let cages = [
+            (9, vec![(2, 3), (3, 3)]),
+            (7, vec![(4, 1), (4, 2)]),
+            (8, vec![(4, 0), (5, 0)]),
+            (6, vec![(4, 4), (4, 5)]),
+            (11, vec![(1, 4), (2, 4), (2, 5)]),
+            (6, vec![(5, 4), (5, 5)]),
+            (8, vec![(0, 1), (0, 2)]),
+            (5, vec![(3, 1), (3, 2)]),
+            (7, vec![(3, 4), (3, 5)]),
+            (6, vec![(5, 1), (5, 2)]),
+            (7, vec![(0, 0), (1, 0)]),
+            (12, vec![(0, 4), (0, 5), (1, 5)]),
+            (5, vec![(1, 2), (2, 2)]),
+            (12, vec![(1, 1), (2, 0), (2, 1)]),
+            (9, vec![(4, 3), (5, 3)]),
+            (5, vec![(3, 0)]),
+            (3, vec![(0, 3), (1, 3)]),
+        ];
+        let constraints: Vec<Constraint> = cages
+            .into_iter()
+            .map(|(sum, cells)| Constraint::Cage { sum, cells })
+            .collect();
+        let sudoku = Sudoku::from_string("300000000000000000000000000000000000")
+            .unwrap()
+            .with_constraints(constraints)
+            .unwrap();
+
+        // Cage sums make the solution unique.
+        assert_every_strategy(&sudoku, Some("326145451263164532532416243651615324"));
+    }
+
+    #[test]
+    fn test_unsolvable_killer_with_every_strategy() {
+        // Without the cage this has solutions; with it, none.
+        let sudoku = Sudoku::from_string("0004000000030200")
+            .unwrap()
+            .with_constraints(vec![Constraint::Cage {
+                sum: 6,
+                cells: vec![(1, 0), (1, 1)],
+            }])
+            .unwrap();
+
+        assert_every_strategy(&sudoku, None);
+    }
+
+    #[test]
+    fn test_solve_variants_with_every_strategy() {
+        let puzzles = [
+            (
+                "204007000387000000900000230001050304048000700600709500800070010079802000062000070",
+                vec![Constraint::AntiKnight],
+                "254367198387291456916584237791658324548123769623749581835476912179832645462915873",
+            ),
+            (
+                "300006000040000000000000600500002030",
+                vec![Constraint::AntiKing],
+                "324156165342431265256413613524542631",
+            ),
+            (
+                "000000600000004005000000000000000000",
+                vec![
+                    Constraint::NonConsecutive,
+                    Constraint::Even {
+                        cells: vec![
+                            (1, 0),
+                            (1, 2),
+                            (2, 2),
+                            (3, 4),
+                            (4, 3),
+                            (4, 5),
+                            (5, 1),
+                            (5, 2),
+                        ],
+                    },
+                    Constraint::Odd {
+                        cells: vec![(0, 0), (0, 1), (3, 1), (4, 0)],
+                    },
+                ],
+                "315246642513264135531462153624426351",
+            ),
+        ];
+
+        // Each set of constraints makes its solution unique.
+        for (puzzle, constraints, expected_solution) in puzzles {
+            let sudoku = Sudoku::from_string(puzzle)
+                .unwrap()
+                .with_constraints(constraints)
+                .unwrap();
+            assert_every_strategy(&sudoku, Some(expected_solution));
+        }
+    }
+
+    #[test]
+    fn test_solve_lines_and_dots_with_every_strategy() {
+        let mut constraints = vec![
+            Constraint::Thermometer {
+                cells: vec![(0, 3), (0, 4), (0, 5)],
+            },
+            Constraint::Thermometer {
+                cells: vec![(1, 4), (1, 5), (2, 5), (2, 4)],
+            },
+            Constraint::Arrow {
+                circle: (0, 2),
+                cells: vec![(1, 2), (1, 1)],
+            },
+            Constraint::Arrow {
+                circle: (5, 2),
+                cells: vec![(4, 2), (3, 2)],
+            },
+        ];
+        let white = [
+            [(0, 4), (0, 5)],
+            [(1, 0), (1, 1)],
+            [(1, 5), (2, 5)],
+            [(2, 2), (3, 2)],
+            [(3, 2), (3, 3)],
+            [(4, 3), (5, 3)],
+            [(4, 4), (5, 4)],
+            [(5, 2), (5, 3)],
+        ];
+        let black = [
+            [(0, 3), (0, 4)],
+            [(2, 0), (3, 0)],
+            [(2, 5), (3, 5)],
+            [(4, 0), (4, 1)],
+            [(4, 4), (4, 5)],
+            [(5, 0), (5, 1)],
+        ];
+        constraints.extend(white.map(|cells| Constraint::WhiteDot { cells }));
+        constraints.extend(black.map(|cells| Constraint::BlackDot { cells }));
+        let sudoku = Sudoku::from_string("000000000000000000060000000000000000")
+            .unwrap()
+            .with_constraints(constraints)
+            .unwrap();
+
+        assert_every_strategy(&sudoku, Some("316245542613253164164352421536635421"));
+    }
+
+    #[test]
+    fn test_unsolvable_lines_and_dots_with_every_strategy() {
+        let puzzles = [
+            (
+                "0300000200000040",
+                Constraint::Arrow {
+                    circle: (2, 0),
+                    cells: vec![(2, 1), (2, 2)],
+                },
+            ),
+            (
+                "0004000002000000",
+                Constraint::Thermometer {
+                    cells: vec![(0, 0), (0, 1), (0, 2)],
+                },
+            ),
+            (
+                "1002000000000000",
+                Constraint::WhiteDot {
+                    cells: [(0, 0), (0, 1)],
+                },
+            ),
+            (
+                "3000000000000000",
+                Constraint::BlackDot {
+                    cells: [(0, 0), (0, 1)],
+                },
+            ),
+        ];
+
+        // Each board has solutions on its own, and none with its constraint.
+        for (puzzle, constraint) in puzzles {
+            let sudoku = Sudoku::from_string(puzzle).unwrap();
+            assert!(Heuristic::solve(&mut sudoku.clone()).is_ok());
+            let sudoku = sudoku.with_constraints(vec![constraint]).expect(puzzle);
+            assert_every_strategy(&sudoku, None);
+        }
+    }
+
+    #[test]
+    fn test_backtracking_gives_up_at_the_deadline() {
+        // Built against naive backtracking, which needs minutes on it.
+        let puzzle =
+            "000000000000003085001020000000507000004000100090000000500000073002010000000040009";
+        let mut sudoku = Sudoku::from_string(puzzle).unwrap();
+
+        let started = Instant::now();
+        let result =
+            BacktrackingSolver::solve_before(&mut sudoku, started + Duration::from_millis(50));
+        assert_eq!(result.unwrap_err().to_string(), "Ran out of time");
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(sudoku.to_string(), puzzle, "Left as it was");
+
+        let mut sudoku = Sudoku::from_string(
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079",
+        )
+        .unwrap();
+        let deadline = Instant::now() + Duration::from_secs(60);
+        assert!(BacktrackingSolver::solve_before(&mut sudoku, deadline).is_ok());
+        assert!(Validator::is_valid_board(&sudoku));
+    }
 }
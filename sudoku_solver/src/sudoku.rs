@@ -1,104 +1,445 @@
-use crate::validator::Validator;
+use crate::cell::{Cell, Digit};
+use crate::constraint::Constraint;
+use crate::regions::Regions;
+use crate::shape::Shape;
+use crate::validator::{Board, Validator};
 use anyhow::Result;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, Serializer};
+use std::cmp::Ordering;
 use std::fmt;
-use std::ops::{Deref, DerefMut};
+use std::hash::{Hash, Hasher};
+use std::ops::Index;
 
-#[derive(Debug, Deserialize)]
+// Cells are stored inline, row by row, in an array large enough for the
+// biggest supported board, so boards never allocate. That costs 1250 bytes of
+// digits and givens for every board, 9x9 included, copied on each clone; the
+// `clone` bench in benches/solvers.rs tracks it. Digits present when a board
+// is created are its givens and cannot be changed afterwards.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "SudokuData")]
 pub struct Sudoku {
+    shape: Shape,
+    cells: [u8; Shape::MAX_CELLS],
+    givens: [bool; Shape::MAX_CELLS],
+    regions: Option<Regions>,
+    constraints: Vec<Constraint>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SudokuData {
     board: Vec<Vec<u8>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    box_rows: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    box_cols: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    regions: Option<Vec<Vec<u8>>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    constraints: Vec<Constraint>,
+}
+
+impl TryFrom<SudokuData> for Sudoku {
+    type Error = anyhow::Error;
+
+    fn try_from(data: SudokuData) -> Result<Self> {
+        let shape = match (data.box_rows, data.box_cols) {
+            (Some(box_rows), Some(box_cols)) => Shape::new(box_rows, box_cols)?,
+            (None, None) => Shape::for_size(data.board.len())?,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "box_rows and box_cols must be given together"
+                ));
+            }
+        };
+
+        let mut sudoku = Self::fill(shape, &data.board)?;
+        if let Some(rows) = data.regions {
+            sudoku.regions = Some(Self::check_regions(shape, Regions::new(&rows)?)?);
+        }
+        Constraint::check_all(shape, &data.constraints)?;
+        sudoku.constraints = data.constraints;
+        Ok(sudoku)
+    }
+}
+
+// Box dimensions are only written when they differ from the default for the
+// board size.
+impl Serialize for Sudoku {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let custom_boxes = Shape::for_size(self.size()).ok() != Some(self.shape);
+        SudokuData {
+            board: self.rows().map(<[u8]>::to_vec).collect(),
+            box_rows: custom_boxes.then_some(self.shape.box_rows()),
+            box_cols: custom_boxes.then_some(self.shape.box_cols()),
+            regions: self.regions.as_ref().map(Regions::rows),
+            constraints: self.constraints.clone(),
+        }
+        .serialize(serializer)
+    }
 }
 
 impl Sudoku {
     pub fn new(board: Vec<Vec<u8>>) -> Result<Self> {
-        if !Validator::is_valid_board(&board) {
+        let shape = Shape::for_size(board.len())?;
+        Self::with_shape(board, shape)
+    }
+
+    pub fn with_shape(board: Vec<Vec<u8>>, shape: Shape) -> Result<Self> {
+        let sudoku = Self::fill(shape, &board)?;
+        if !Validator::is_valid_board(&sudoku) {
+            return Err(anyhow::anyhow!("Invalid board"));
+        }
+
+        Ok(sudoku)
+    }
+
+    pub fn from_array(board: [[u8; 9]; 9]) -> Result<Self> {
+        let mut sudoku = Self::empty(Shape::CLASSIC);
+        for (row, values) in sudoku.rows_mut().zip(&board) {
+            row.copy_from_slice(values);
+        }
+        sudoku.mark_givens();
+        if !Validator::is_valid_board(&sudoku) {
+            return Err(anyhow::anyhow!("Invalid board"));
+        }
+
+        Ok(sudoku)
+    }
+
+    pub fn empty(shape: Shape) -> Self {
+        Self {
+            shape,
+            cells: [0; Shape::MAX_CELLS],
+            givens: [false; Shape::MAX_CELLS],
+            regions: None,
+            constraints: Vec::new(),
+        }
+    }
+
+    // A board whose boxes are the given jigsaw regions.
+    pub fn jigsaw(board: Vec<Vec<u8>>, regions: Regions) -> Result<Self> {
+        let shape = Shape::for_size(board.len())?;
+        let mut sudoku = Self::fill(shape, &board)?;
+        sudoku.regions = Some(Self::check_regions(shape, regions)?);
+        if !Validator::is_valid_board(&sudoku) {
+            return Err(anyhow::anyhow!("Invalid board"));
+        }
+
+        Ok(sudoku)
+    }
+
+    fn check_regions(shape: Shape, regions: Regions) -> Result<Regions> {
+        if regions.size() != shape.size() {
+            return Err(anyhow::anyhow!(
+                "Region map is {0}x{0} but the board is {1}x{1}",
+                regions.size(),
+                shape.size()
+            ));
+        }
+        Ok(regions)
+    }
+
+    pub fn regions(&self) -> Option<&Regions> {
+        self.regions.as_ref()
+    }
+
+    pub fn with_constraints(mut self, constraints: Vec<Constraint>) -> Result<Self> {
+        Constraint::check_all(self.shape, &constraints)?;
+        self.constraints = constraints;
+        if !Validator::is_valid_board(&self) {
+            return Err(anyhow::anyhow!("Invalid board"));
+        }
+
+        Ok(self)
+    }
+
+    pub fn constraints(&self) -> &[Constraint] {
+        &self.constraints
+    }
+
+    // Copies the rows in after checking dimensions and digit range only.
+    fn fill(shape: Shape, board: &[Vec<u8>]) -> Result<Self> {
+        let size = shape.size();
+        if board.len() != size || board.iter().any(|row| row.len() != size) {
+            return Err(anyhow::anyhow!("Board must be {}x{}", size, size));
+        }
+        if board.iter().flatten().any(|&value| value as usize > size) {
             return Err(anyhow::anyhow!("Invalid board"));
         }
 
-        Ok(Self { board })
+        let mut sudoku = Self::empty(shape);
+        for (row, values) in sudoku.rows_mut().zip(board) {
+            row.copy_from_slice(values);
+        }
+        sudoku.mark_givens();
+        Ok(sudoku)
     }
 
-    fn contains_non_digit(input: &str) -> bool {
-        !input.chars().all(|c| c.is_digit(10))
+    fn mark_givens(&mut self) {
+        for (given, &value) in self.givens.iter_mut().zip(&self.cells) {
+            *given = value != 0;
+        }
     }
 
+    // The board size is taken from the length: 16, 36, 81, 144, 256 or 625.
     pub fn from_string(input: &str) -> Result<Self> {
-        const SUDOKU_BOARD: usize = 9 * 9;
-        if input.len() != SUDOKU_BOARD || Self::contains_non_digit(input) {
+        let len = input.chars().count();
+        let size = (1..=Shape::MAX_SIZE)
+            .find(|size| size * size == len)
+            .ok_or_else(|| anyhow::anyhow!("Invalid input string"))?;
+
+        Self::from_string_with_shape(input, Shape::for_size(size)?)
+    }
+
+    pub fn from_string_with_shape(input: &str, shape: Shape) -> Result<Self> {
+        let values: Vec<u8> = input
+            .chars()
+            .map(Shape::parse_symbol)
+            .collect::<Option<_>>()
+            .ok_or_else(|| anyhow::anyhow!("Invalid input string"))?;
+        if values.len() != shape.cell_count() {
             return Err(anyhow::anyhow!("Invalid input string"));
         }
 
-        let board = input
-            .chars()
-            .map(|c| c.to_digit(10).unwrap() as u8)
-            .collect::<Vec<u8>>()
-            .chunks(9)
-            .map(|chunk| chunk.to_vec())
-            .collect::<Vec<Vec<u8>>>();
+        let board = values.chunks(shape.size()).map(<[u8]>::to_vec).collect();
+        Self::with_shape(board, shape)
+    }
+
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    pub fn size(&self) -> usize {
+        self.shape.size()
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        self.cells[..self.shape.cell_count()].chunks(self.shape.size())
+    }
+
+    fn rows_mut(&mut self) -> impl Iterator<Item = &mut [u8]> {
+        let (count, size) = (self.shape.cell_count(), self.shape.size());
+        self.cells[..count].chunks_mut(size)
+    }
+
+    pub fn get(&self, cell: Cell) -> Option<Digit> {
+        if !self.contains(cell) {
+            return None;
+        }
+        Digit::new(self.cells[self.index_of(cell)]).ok()
+    }
+
+    pub fn set(&mut self, cell: Cell, digit: Digit) -> Result<()> {
+        if digit.get() as usize > self.size() {
+            return Err(anyhow::anyhow!(
+                "{} is not a digit on a {}x{} board",
+                digit,
+                self.size(),
+                self.size()
+            ));
+        }
+        self.write(cell, digit.get())
+    }
+
+    pub fn clear(&mut self, cell: Cell) -> Result<()> {
+        self.write(cell, 0)
+    }
+
+    fn write(&mut self, cell: Cell, value: u8) -> Result<()> {
+        if !self.contains(cell) {
+            return Err(anyhow::anyhow!("{} is off the board", cell));
+        }
+        if self.is_given(cell) {
+            return Err(anyhow::anyhow!("{} is a given", cell));
+        }
+
+        let index = self.index_of(cell);
+        self.cells[index] = value;
+        Ok(())
+    }
+
+    pub fn is_given(&self, cell: Cell) -> bool {
+        self.contains(cell) && self.givens[self.index_of(cell)]
+    }
+
+    pub fn contains(&self, cell: Cell) -> bool {
+        cell.row() < self.size() && cell.col() < self.size()
+    }
+
+    fn index_of(&self, cell: Cell) -> usize {
+        cell.row() * self.size() + cell.col()
+    }
+
+    fn cell_at(&self, index: usize) -> (Cell, Option<Digit>) {
+        let cell = Cell::new(index / self.size(), index % self.size())
+            .expect("Board cells are within the largest size");
+        (cell, Digit::new(self.cells[index]).ok())
+    }
+
+    pub fn row_cells(&self, row: usize) -> impl Iterator<Item = (Cell, Option<Digit>)> + '_ {
+        (0..self.size()).map(move |col| self.cell_at(row * self.size() + col))
+    }
+
+    pub fn column_cells(&self, col: usize) -> impl Iterator<Item = (Cell, Option<Digit>)> + '_ {
+        (0..self.size()).map(move |row| self.cell_at(row * self.size() + col))
+    }
 
-        Self::new(board)
+    // Boxes, or jigsaw regions, are numbered row by row, like cells within a
+    // box.
+    pub fn box_cells(&self, b: usize) -> impl Iterator<Item = (Cell, Option<Digit>)> + '_ {
+        (0..self.size()).map(move |i| {
+            let (row, col) = match &self.regions {
+                Some(regions) => regions.cells(b)[i],
+                None => self.shape.box_cell(b, i),
+            };
+            self.cell_at(row * self.size() + col)
+        })
     }
 
+    // Used by the solvers, which only ever fill cells that are still empty.
+    pub(crate) fn fill_cell(&mut self, row: usize, col: usize, value: u8) {
+        debug_assert!(
+            !self.givens[row * self.size() + col],
+            "Solvers never overwrite givens"
+        );
+        self.cells[row * self.size() + col] = value;
+    }
+
+    // The original puzzle: the givens alone, with every other cell empty.
+    pub fn puzzle(&self) -> Sudoku {
+        let mut puzzle = Self::empty(self.shape);
+        puzzle.regions = self.regions.clone();
+        puzzle.constraints = self.constraints.clone();
+        for (cell, &given) in self.givens.iter().enumerate().filter(|(_, given)| **given) {
+            puzzle.cells[cell] = self.cells[cell];
+            puzzle.givens[cell] = given;
+        }
+        puzzle
+    }
+
+    // Digits filled in after the board was created.
+    pub fn filled_cells(&self) -> impl Iterator<Item = (Cell, Digit)> + '_ {
+        (0..self.shape.cell_count())
+            .filter(|&index| !self.givens[index])
+            .filter_map(|index| match self.cell_at(index) {
+                (cell, Some(digit)) => Some((cell, digit)),
+                (_, None) => None,
+            })
+    }
+
+    #[allow(clippy::inherent_to_string_shadow_display)]
     pub fn to_string(&self) -> String {
-        self.board
-            .iter()
-            .flatten()
-            .map(|&n| n.to_string())
-            .collect()
+        self.rows().flatten().map(|&n| Shape::symbol(n)).collect()
     }
 }
 
-// Implementing the Deref and DerefMut traits allows us to use Sudoku as a slice
-// of Vec<Vec<u8>>. This is useful for passing the Sudoku instance to functions
-// that expect a slice, such as the `solve` method in the Solver trait.
-// This allows us to use Sudoku as a slice of Vec<Vec<u8>>. This is useful for passing the Sudoku instance to functions
-impl Deref for Sudoku {
-    type Target = Vec<Vec<u8>>;
+// `sudoku[row]` is the row as a slice, so cells read as `sudoku[row][col]`.
+impl Index<usize> for Sudoku {
+    type Output = [u8];
 
-    fn deref(&self) -> &Self::Target {
-        &self.board
+    fn index(&self, row: usize) -> &Self::Output {
+        let size = self.shape.size();
+        &self.cells[row * size..(row + 1) * size]
     }
 }
 
-impl DerefMut for Sudoku {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.board
+impl Board for Sudoku {
+    fn shape(&self) -> Option<Shape> {
+        Some(self.shape)
+    }
+
+    fn value(&self, row: usize, col: usize) -> u8 {
+        self.cells[row * self.shape.size() + col]
+    }
+
+    fn regions(&self) -> Option<&Regions> {
+        self.regions.as_ref()
+    }
+
+    fn constraints(&self) -> &[Constraint] {
+        &self.constraints
     }
 }
 
+// Boards compare by their digits and rules, not by which digits were given,
+// so a solved puzzle equals the same solution given in full. Ordering follows
+// the shape, then the digits row by row.
 impl PartialEq for Sudoku {
     fn eq(&self, other: &Self) -> bool {
-        self.board == other.board
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Sudoku {}
+
+impl Ord for Sudoku {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.shape
+            .cmp(&other.shape)
+            .then_with(|| self.cells.cmp(&other.cells))
+            .then_with(|| self.regions.cmp(&other.regions))
+            .then_with(|| self.constraints.cmp(&other.constraints))
+    }
+}
+
+impl PartialOrd for Sudoku {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for Sudoku {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.shape.hash(state);
+        self.cells[..self.shape.cell_count()].hash(state);
+        self.regions.hash(state);
+        self.constraints.hash(state);
     }
 }
 
+// `{:#}` shows givens in bold so they stand out from solved cells. Jigsaw
+// regions are not drawn.
 impl fmt::Display for Sudoku {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "+-----------------------+")?;
-        for (i, row) in self.board.iter().enumerate() {
-            if i % 3 == 0 && i != 0 {
-                writeln!(f, "|-------+-------+-------|")?;
+        let (box_rows, box_cols) = match self.regions {
+            Some(_) => (self.size(), self.size()),
+            None => (self.shape.box_rows(), self.shape.box_cols()),
+        };
+        let boxes_across = self.size() / box_cols;
+        let border = format!("+{}+", "-".repeat(self.size() * 2 + boxes_across * 2 - 1));
+        let separator = format!(
+            "|{}|",
+            vec!["-".repeat(box_cols * 2 + 1); boxes_across].join("+")
+        );
+
+        writeln!(f, "{}", border)?;
+        for (i, row) in self.rows().enumerate() {
+            if i % box_rows == 0 && i != 0 {
+                writeln!(f, "{}", separator)?;
             }
             write!(f, "| ")?;
             for (j, &num) in row.iter().enumerate() {
-                if j % 3 == 0 && j != 0 {
+                if j % box_cols == 0 && j != 0 {
                     write!(f, "| ")?;
                 }
                 if num == 0 {
                     write!(f, ". ")?;
+                } else if f.alternate() && self.givens[i * self.size() + j] {
+                    write!(f, "\x1b[1m{}\x1b[0m ", Shape::symbol(num))?;
                 } else {
-                    write!(f, "{} ", num)?;
+                    write!(f, "{} ", Shape::symbol(num))?;
                 }
             }
             writeln!(f, "|")?;
         }
-        writeln!(f, "+-----------------------+")
+        writeln!(f, "{}", border)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::solvers::solver::{SolverKind, solve_with_strategy};
 
     #[test]
     fn test_valid_board() {
@@ -116,7 +457,14 @@ mod tests {
 
         let result = Sudoku::new(valid_board.clone());
         assert!(result.is_ok(), "Expected valid board");
-        assert_eq!(result.unwrap().board, valid_board);
+        assert_eq!(
+            result
+                .unwrap()
+                .rows()
+                .map(<[u8]>::to_vec)
+                .collect::<Vec<_>>(),
+            valid_board
+        );
     }
 
     #[test]
@@ -137,6 +485,27 @@ mod tests {
         assert!(result.is_err(), "Expected error for invalid board");
     }
 
+    #[test]
+    fn test_from_array() {
+        let mut board = [[0; 9]; 9];
+        board[0] = [5, 3, 0, 0, 7, 0, 0, 0, 0];
+        let sudoku = Sudoku::from_array(board).unwrap();
+        assert_eq!(sudoku[0][1], 3);
+        assert!(Validator::is_valid(&sudoku, 0, 2, 4));
+
+        board[1][1] = 5;
+        assert!(
+            Sudoku::from_array(board).is_err(),
+            "Expected error for duplicate in box"
+        );
+    }
+
+    #[test]
+    fn test_new_rejects_wrong_dimensions() {
+        let result = Sudoku::new(vec![vec![0; 9]; 8]);
+        assert!(result.is_err(), "Expected error for missing row");
+    }
+
     #[test]
     fn test_from_string_valid() {
         let input =
@@ -220,4 +589,222 @@ mod tests {
         assert!(display.contains("+-----------------------+"));
         assert!(display.contains("| 5 3 . | . 7 . | . . . |"));
     }
+
+    #[test]
+    fn test_from_string_with_letters() {
+        let input = format!("G{}", "0".repeat(255));
+        let sudoku = Sudoku::from_string(&input).unwrap();
+
+        assert_eq!(sudoku.size(), 16);
+        assert_eq!(sudoku[0][0], 16);
+        assert_eq!(sudoku.to_string(), input);
+        assert!(
+            Sudoku::from_string(&format!("5{}", "0".repeat(15))).is_err(),
+            "5 is out of range on a 4x4 board"
+        );
+    }
+
+    #[test]
+    fn test_with_shape_uses_given_boxes() {
+        let input = "123456000000000000000000000000000000";
+        let tall = Sudoku::from_string_with_shape(input, Shape::new(3, 2).unwrap()).unwrap();
+        assert_eq!(tall.shape().box_cols(), 2);
+
+        let mut board = vec![vec![1, 0, 0, 0, 0, 0], vec![0, 0, 1, 0, 0, 0]];
+        board.extend(vec![vec![0; 6]; 4]);
+        assert!(Sudoku::with_shape(board.clone(), Shape::new(3, 2).unwrap()).is_ok());
+        assert!(
+            Sudoku::with_shape(board, Shape::new(2, 3).unwrap()).is_err(),
+            "Both ones share a 2x3 box"
+        );
+    }
+
+    #[test]
+    fn test_deserialize_box_shape() {
+        let json = r#"{"board": [[1,2,3,4,5,6],[0,0,0,0,0,0],[0,0,0,0,0,0],
+            [0,0,0,0,0,0],[0,0,0,0,0,0],[0,0,0,0,0,0]], "box_rows": 3, "box_cols": 2}"#;
+        let sudoku: Sudoku = serde_json::from_str(json).unwrap();
+        assert_eq!(sudoku.shape(), Shape::new(3, 2).unwrap());
+
+        let json = r#"{"board": [[0,0,0,0],[0,0,0,0],[0,0,0,0],[0,0,0,0]], "box_rows": 2}"#;
+        assert!(serde_json::from_str::<Sudoku>(json).is_err());
+    }
+
+    #[test]
+    fn test_display_six_by_six() {
+        let sudoku = Sudoku::from_string("123456000000000000000000000000000000").unwrap();
+        let expected = "\
++---------------+
+| 1 2 3 | 4 5 6 |
+| . . . | . . . |
+|-------+-------|
+| . . . | . . . |
+| . . . | . . . |
+|-------+-------|
+| . . . | . . . |
+| . . . | . . . |
++---------------+
+";
+        assert_eq!(sudoku.to_string().len(), 36);
+        assert_eq!(format!("{}", sudoku), expected);
+    }
+
+    fn cell(row: usize, col: usize) -> Cell {
+        Cell::new(row, col).unwrap()
+    }
+
+    fn digit(value: u8) -> Digit {
+        Digit::new(value).unwrap()
+    }
+
+    #[test]
+    fn test_givens_survive_solving() {
+        let input = "1000001000000000";
+        let mut sudoku = Sudoku::from_string(input).unwrap();
+        solve_with_strategy(&mut sudoku, SolverKind::Backtracking).unwrap();
+
+        assert!(sudoku.is_given(cell(0, 0)));
+        assert!(!sudoku.is_given(cell(0, 1)), "Solved cells are not givens");
+        assert_eq!(sudoku.puzzle().to_string(), input);
+        assert_eq!(sudoku.filled_cells().count(), 14);
+        assert!(
+            sudoku
+                .filled_cells()
+                .all(|(cell, _)| !sudoku.is_given(cell))
+        );
+    }
+
+    #[test]
+    fn test_set_protects_givens() {
+        let mut sudoku = Sudoku::from_string("1000001000000000").unwrap();
+
+        assert!(
+            sudoku.set(cell(0, 0), digit(2)).is_err(),
+            "Givens cannot change"
+        );
+        assert!(
+            sudoku.clear(cell(0, 0)).is_err(),
+            "Givens cannot be cleared"
+        );
+        assert!(
+            sudoku.set(cell(0, 1), digit(5)).is_err(),
+            "5 is out of range"
+        );
+        assert!(
+            sudoku.set(cell(4, 0), digit(1)).is_err(),
+            "Row 5 is off the board"
+        );
+        sudoku.set(cell(0, 1), digit(3)).unwrap();
+        assert_eq!(sudoku.get(cell(0, 1)), Some(digit(3)));
+        sudoku.clear(cell(0, 1)).unwrap();
+        assert_eq!(sudoku.get(cell(0, 1)), None);
+    }
+
+    #[test]
+    fn test_unit_iterators() {
+        let sudoku = Sudoku::from_string("1000001000000000").unwrap();
+
+        let row: Vec<Option<Digit>> = sudoku.row_cells(1).map(|(_, digit)| digit).collect();
+        assert_eq!(row, vec![None, None, Some(digit(1)), None]);
+        let column: Vec<Cell> = sudoku.column_cells(2).map(|(cell, _)| cell).collect();
+        assert_eq!(column, vec![cell(0, 2), cell(1, 2), cell(2, 2), cell(3, 2)]);
+        let boxed: Vec<Cell> = sudoku.box_cells(1).map(|(cell, _)| cell).collect();
+        assert_eq!(boxed, vec![cell(0, 2), cell(0, 3), cell(1, 2), cell(1, 3)]);
+    }
+
+    #[test]
+    fn test_alternate_display_marks_givens() {
+        let mut sudoku = Sudoku::from_string("1000001000000000").unwrap();
+        sudoku.set(cell(0, 1), digit(2)).unwrap();
+
+        let output = format!("{:#}", sudoku);
+        assert!(output.contains("| \x1b[1m1\x1b[0m 2 |"));
+        assert!(!format!("{}", sudoku).contains('\x1b'));
+    }
+
+    #[test]
+    fn test_with_constraints_validates_diagonals() {
+        let mut board = vec![vec![0; 9]; 9];
+        board[0][0] = 1;
+        board[8][8] = 1;
+        board[4][4] = 0;
+        assert!(Sudoku::new(board.clone()).is_ok());
+
+        let result = Sudoku::new(board)
+            .unwrap()
+            .with_constraints(vec![Constraint::Diagonals]);
+        assert!(result.is_err(), "Both ones lie on the main diagonal");
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let sudoku = Sudoku::from_string_with_shape(&"0".repeat(36), Shape::new(3, 2).unwrap())
+            .unwrap()
+            .with_constraints(vec![Constraint::Diagonals])
+            .unwrap();
+
+        let json = serde_json::to_string(&sudoku).unwrap();
+        assert!(json.contains(r#""box_rows":3,"box_cols":2"#));
+        let parsed: Sudoku = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.shape(), sudoku.shape());
+        assert_eq!(parsed.constraints(), [Constraint::Diagonals]);
+
+        let plain = serde_json::to_string(&Sudoku::from_string(&"0".repeat(16)).unwrap()).unwrap();
+        assert!(!plain.contains("box_rows") && !plain.contains("constraints"));
+    }
+
+    #[test]
+    fn test_jigsaw_regions_replace_boxes() {
+        let regions = Regions::new(&[
+            vec![0, 0, 1, 1],
+            vec![0, 0, 1, 1],
+            vec![2, 3, 3, 3],
+            vec![2, 2, 2, 3],
+        ])
+        .unwrap();
+        let board = vec![vec![0; 4], vec![0; 4], vec![0, 1, 0, 0], vec![1, 0, 0, 0]];
+        assert!(Sudoku::new(board.clone()).is_err(), "Ones share a 2x2 box");
+
+        let sudoku = Sudoku::jigsaw(board, regions.clone()).unwrap();
+        let region: Vec<Cell> = sudoku.box_cells(2).map(|(cell, _)| cell).collect();
+        assert_eq!(region, vec![cell(2, 0), cell(3, 0), cell(3, 1), cell(3, 2)]);
+        assert!(!Validator::is_valid(&sudoku, 3, 2, 1), "Region 2 holds a 1");
+        assert!(Validator::is_valid(&sudoku, 2, 3, 2));
+
+        let json = serde_json::to_string(&sudoku).unwrap();
+        let parsed: Sudoku = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.regions(), Some(&regions));
+
+        let repeated = vec![vec![0; 4], vec![0; 4], vec![1, 0, 0, 0], vec![0, 1, 0, 0]];
+        assert!(Sudoku::jigsaw(repeated, regions).is_err());
+        assert!(
+            Sudoku::jigsaw(
+                vec![vec![0; 9]; 9],
+                Regions::from_shape(Shape::new(2, 2).unwrap())
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_boards_compare_and_hash_by_digits() {
+        let puzzle =
+            "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+        let sudoku = Sudoku::from_string(puzzle).unwrap();
+        let mut filled = sudoku.clone();
+        filled.fill_cell(0, 2, 4);
+
+        assert_eq!(sudoku.clone(), sudoku);
+        assert_ne!(filled, sudoku);
+        assert!(sudoku < filled, "An empty cell sorts before a digit");
+
+        let set: std::collections::HashSet<Sudoku> =
+            [sudoku.clone(), filled.clone(), sudoku.clone()].into();
+        assert_eq!(set.len(), 2);
+        let diagonal = sudoku
+            .clone()
+            .with_constraints(vec![Constraint::Diagonals])
+            .unwrap();
+        assert_ne!(diagonal, sudoku, "Rules are compared too");
+    }
 }
@@ -0,0 +1,634 @@
+use std::{cmp::Ordering, time::Instant};
+
+use anyhow::{Result, anyhow};
+
+use crate::{shape::Shape, sudoku::Sudoku};
+
+// A relabelling of a board that keeps it valid: rows and columns permuted
+// within their bands and stacks, whole bands and stacks permuted, an
+// optional transposition and a digit relabelling. Rotations and reflections
+// are combinations of these.
+//
+// Cell (row, col) of the result comes from cell (rows[row], cols[col]) of
+// the board, or (rows[col], cols[row]) when transposed, with its digit
+// renamed through `digits`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Transform {
+    shape: Shape,
+    transpose: bool,
+    rows: Vec<usize>,
+    cols: Vec<usize>,
+    digits: Vec<u8>,
+}
+
+impl Transform {
+    // Leaves boards of this shape unchanged.
+    pub fn identity(shape: Shape) -> Self {
+        Self {
+            shape,
+            transpose: false,
+            rows: (0..shape.size()).collect(),
+            cols: (0..shape.size()).collect(),
+            digits: (0..=shape.size() as u8).collect(),
+        }
+    }
+
+    // Shape of the boards this transform applies to.
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    // Shape of the boards it produces: boxes turn on their side when
+    // transposed.
+    pub fn output_shape(&self) -> Shape {
+        if self.transpose {
+            Shape::new(self.shape.box_cols(), self.shape.box_rows())
+                .expect("Transposed boxes are a valid shape")
+        } else {
+            self.shape
+        }
+    }
+
+    // This transform followed by `next`, which applies to the boards this one
+    // produces.
+    pub fn then(&self, next: &Transform) -> Transform {
+        let (first, second) = if self.transpose {
+            (&next.cols, &next.rows)
+        } else {
+            (&next.rows, &next.cols)
+        };
+        Transform {
+            shape: self.shape,
+            transpose: self.transpose != next.transpose,
+            rows: first.iter().map(|&i| self.rows[i]).collect(),
+            cols: second.iter().map(|&i| self.cols[i]).collect(),
+            digits: self
+                .digits
+                .iter()
+                .map(|&digit| next.digits[digit as usize])
+                .collect(),
+        }
+    }
+
+    // Undoes this transform.
+    pub fn inverse(&self) -> Transform {
+        let invert = |order: &[usize]| {
+            let mut inverse = vec![0; order.len()];
+            for (i, &j) in order.iter().enumerate() {
+                inverse[j] = i;
+            }
+            inverse
+        };
+        let mut digits = vec![0; self.digits.len()];
+        for (old, &new) in self.digits.iter().enumerate() {
+            digits[new as usize] = old as u8;
+        }
+        let (rows, cols) = if self.transpose {
+            (invert(&self.cols), invert(&self.rows))
+        } else {
+            (invert(&self.rows), invert(&self.cols))
+        };
+        Transform {
+            shape: self.output_shape(),
+            transpose: self.transpose,
+            rows,
+            cols,
+            digits,
+        }
+    }
+
+    pub fn transpose(self) -> Self {
+        let mut step = Self::identity(self.output_shape());
+        step.transpose = true;
+        self.then(&step)
+    }
+
+    // A quarter turn clockwise.
+    pub fn rotate(self) -> Self {
+        self.flip().transpose()
+    }
+
+    // Reflects left to right.
+    pub fn mirror(self) -> Self {
+        let mut step = Self::identity(self.output_shape());
+        step.cols.reverse();
+        self.then(&step)
+    }
+
+    // Reflects top to bottom.
+    pub fn flip(self) -> Self {
+        let mut step = Self::identity(self.output_shape());
+        step.rows.reverse();
+        self.then(&step)
+    }
+
+    // Swaps two rows of the same band.
+    pub fn swap_rows(self, a: usize, b: usize) -> Result<Self> {
+        let box_rows = self.output_shape().box_rows();
+        self.swap_lines(a, b, 1, box_rows, false)
+    }
+
+    // Swaps two columns of the same stack.
+    pub fn swap_columns(self, a: usize, b: usize) -> Result<Self> {
+        let box_cols = self.output_shape().box_cols();
+        self.swap_lines(a, b, 1, box_cols, true)
+    }
+
+    // Swaps two bands, the rows of boxes, counted from the top.
+    pub fn swap_bands(self, a: usize, b: usize) -> Result<Self> {
+        let (box_rows, size) = (self.output_shape().box_rows(), self.shape.size());
+        self.swap_lines(a, b, box_rows, size, false)
+    }
+
+    // Swaps two stacks, the columns of boxes, counted from the left.
+    pub fn swap_stacks(self, a: usize, b: usize) -> Result<Self> {
+        let (box_cols, size) = (self.output_shape().box_cols(), self.shape.size());
+        self.swap_lines(a, b, box_cols, size, true)
+    }
+
+    // Swaps two blocks of `width` lines, which must lie in the same group of
+    // `group` lines.
+    fn swap_lines(
+        self,
+        a: usize,
+        b: usize,
+        width: usize,
+        group: usize,
+        cols: bool,
+    ) -> Result<Self> {
+        let size = self.shape.size();
+        if (a.max(b) + 1) * width > size || a * width / group != b * width / group {
+            return Err(anyhow!("Cannot swap {} and {}", a + 1, b + 1));
+        }
+
+        let mut step = Self::identity(self.output_shape());
+        let order = if cols { &mut step.cols } else { &mut step.rows };
+        for i in 0..width {
+            order.swap(a * width + i, b * width + i);
+        }
+        Ok(self.then(&step))
+    }
+
+    // Renames digit `d` to `digits[d - 1]`.
+    pub fn relabel(self, digits: &[u8]) -> Result<Self> {
+        let size = self.shape.size();
+        let mut sorted = digits.to_vec();
+        sorted.sort_unstable();
+        if sorted != (1..=size as u8).collect::<Vec<_>>() {
+            return Err(anyhow!("Digits must be a permutation of 1 to {}", size));
+        }
+
+        let mut step = Self::identity(self.output_shape());
+        step.digits[1..].copy_from_slice(digits);
+        Ok(self.then(&step))
+    }
+
+    // A transform picked from `seed`: bands, stacks, the lines within them
+    // and the digits shuffled, and a coin flip for transposing. Together these
+    // reach every rotation and reflection. Transposition is skipped for
+    // boxes that are not square, so the shape stays the same. The same seed
+    // always gives the same transform.
+    pub fn random(shape: Shape, seed: u64) -> Self {
+        let mut rng = SplitMix64(seed);
+        let shuffled = |rng: &mut SplitMix64, group: usize, width: usize| {
+            let mut order = Vec::with_capacity(group * width);
+            for block in rng.shuffle((0..group).collect()) {
+                order.extend(rng.shuffle((0..width).map(|i| block * width + i).collect()));
+            }
+            order
+        };
+
+        let size = shape.size();
+        let rows = shuffled(&mut rng, size / shape.box_rows(), shape.box_rows());
+        let cols = shuffled(&mut rng, size / shape.box_cols(), shape.box_cols());
+        let mut digits = vec![0];
+        digits.extend(rng.shuffle((1..=size as u8).collect()));
+        let transpose = shape.box_rows() == shape.box_cols() && rng.next() & 1 == 1;
+        Self {
+            shape,
+            transpose,
+            rows,
+            cols,
+            digits,
+        }
+    }
+
+    // Boards with jigsaw regions or variant constraints are not supported,
+    // since these symmetries do not preserve them.
+    pub fn apply(&self, board: &Sudoku) -> Result<Sudoku> {
+        if board.shape() != self.shape {
+            return Err(anyhow!("Transform does not fit the board shape"));
+        }
+        if board.regions().is_some() || !board.constraints().is_empty() {
+            return Err(anyhow!("Symmetries only apply to plain boards"));
+        }
+
+        let map = |board: &Sudoku| -> Vec<Vec<u8>> {
+            (0..self.shape.size())
+                .map(|row| {
+                    (0..self.shape.size())
+                        .map(|col| {
+                            let (r, c) = if self.transpose {
+                                (self.rows[col], self.cols[row])
+                            } else {
+                                (self.rows[row], self.cols[col])
+                            };
+                            self.digits[board[r][c] as usize]
+                        })
+                        .collect()
+                })
+                .collect()
+        };
+        // Givens stay givens; filled cells are filled in afterwards.
+        let mut result = Sudoku::with_shape(map(&board.puzzle()), self.output_shape())?;
+        for (row, values) in map(board).iter().enumerate() {
+            for (col, &value) in values.iter().enumerate() {
+                if result[row][col] == 0 && value != 0 {
+                    result.fill_cell(row, col, value);
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+// A random isomorph of `board` from `seed`, and the transform that made it.
+// Applying the same transform to the solution solves the new puzzle.
+pub fn randomize(board: &Sudoku, seed: u64) -> Result<(Sudoku, Transform)> {
+    let transform = Transform::random(board.shape(), seed);
+    Ok((transform.apply(board)?, transform))
+}
+
+// The least board, read row by row with empty cells first, among all boards
+// a transform reaches from `board`, and the transform that reaches it.
+// Equivalent boards share a canonical form. Transposition is only tried for
+// square boxes, so the shape never changes. The search covers every column
+// arrangement, which limits it to boards up to 9x9.
+pub fn canonical_form(board: &Sudoku) -> Result<(Sudoku, Transform)> {
+    search_canonical_form(board, None)
+}
+
+// Like `canonical_form`, but gives up once `deadline` passes. Sparse boards
+// can take seconds.
+pub fn canonical_form_before(board: &Sudoku, deadline: Instant) -> Result<(Sudoku, Transform)> {
+    search_canonical_form(board, Some(deadline))
+}
+
+fn search_canonical_form(board: &Sudoku, deadline: Option<Instant>) -> Result<(Sudoku, Transform)> {
+    let shape = board.shape();
+    if shape.size() > 9 {
+        return Err(anyhow!("Canonical forms are limited to boards up to 9x9"));
+    }
+
+    let mut search = Search {
+        shape,
+        grid: Vec::new(),
+        cols: Vec::new(),
+        best: None,
+        best_rows: Vec::new(),
+        deadline,
+        timed_out: false,
+    };
+    let transposes: &[bool] = if shape.box_rows() == shape.box_cols() {
+        &[false, true]
+    } else {
+        &[false]
+    };
+    for &transpose in transposes {
+        search.grid = (0..shape.size())
+            .map(|row| {
+                (0..shape.size())
+                    .map(|col| {
+                        if transpose {
+                            board[col][row]
+                        } else {
+                            board[row][col]
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        for cols in arrangements(shape.size(), shape.box_cols()) {
+            search.cols = cols;
+            search.rows(transpose, &mut Vec::new(), [0; Shape::MAX_SIZE + 1], 0);
+        }
+    }
+    if search.timed_out {
+        return Err(anyhow!("Ran out of time"));
+    }
+
+    let transform = search.best.expect("Some arrangement is always found");
+    Ok((transform.apply(board)?, transform))
+}
+
+// Whether one board can be turned into the other.
+pub fn equivalent(a: &Sudoku, b: &Sudoku) -> Result<bool> {
+    Ok(canonical_form(a)?.0.to_string() == canonical_form(b)?.0.to_string())
+}
+
+// Branch and bound over row orders for a fixed column order. `best_rows`
+// holds the rows of the least board found so far, made by `best`.
+struct Search {
+    shape: Shape,
+    grid: Vec<Vec<u8>>,
+    cols: Vec<usize>,
+    best: Option<Transform>,
+    best_rows: Vec<Vec<u8>>,
+    deadline: Option<Instant>,
+    timed_out: bool,
+}
+
+impl Search {
+    // `labels` renames digits in order of first appearance. A branch is cut
+    // as soon as a row comes out greater than the best board's row there.
+    fn rows(
+        &mut self,
+        transpose: bool,
+        rows: &mut Vec<usize>,
+        labels: [u8; Shape::MAX_SIZE + 1],
+        next_label: u8,
+    ) {
+        self.timed_out = self.timed_out
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline);
+        if self.timed_out {
+            return;
+        }
+        let (size, box_rows) = (self.shape.size(), self.shape.box_rows());
+        let depth = rows.len();
+        if depth == size {
+            self.best = Some(self.transform(transpose, rows, labels, next_label));
+            return;
+        }
+
+        // A new band may be any unused one; otherwise stay in the band.
+        let candidates: Vec<usize> = match depth % box_rows {
+            0 => (0..size)
+                .filter(|&row| rows.iter().all(|&used| used / box_rows != row / box_rows))
+                .collect(),
+            _ => {
+                let band = rows[depth - 1] / box_rows * box_rows;
+                (band..band + box_rows)
+                    .filter(|row| !rows.contains(row))
+                    .collect()
+            }
+        };
+        for row in candidates {
+            let (mut labels, mut next_label) = (labels, next_label);
+            let values: Vec<u8> = self
+                .cols
+                .iter()
+                .map(|&col| match self.grid[row][col] {
+                    0 => 0,
+                    digit => {
+                        if labels[digit as usize] == 0 {
+                            next_label += 1;
+                            labels[digit as usize] = next_label;
+                        }
+                        labels[digit as usize]
+                    }
+                })
+                .collect();
+
+            match self.best_rows.get(depth).map(|best| values.cmp(best)) {
+                Some(Ordering::Greater) => continue,
+                Some(Ordering::Equal) => {}
+                // Everything below this row has to be found again.
+                Some(Ordering::Less) | None => {
+                    self.best_rows.truncate(depth);
+                    self.best_rows.push(values);
+                }
+            }
+            rows.push(row);
+            self.rows(transpose, rows, labels, next_label);
+            rows.pop();
+        }
+    }
+
+    fn transform(
+        &self,
+        transpose: bool,
+        rows: &[usize],
+        mut labels: [u8; Shape::MAX_SIZE + 1],
+        mut next_label: u8,
+    ) -> Transform {
+        let size = self.shape.size();
+        // Digits missing from the board take the remaining labels in order.
+        for label in labels[1..=size].iter_mut().filter(|label| **label == 0) {
+            next_label += 1;
+            *label = next_label;
+        }
+        let (rows, cols) = if transpose {
+            (self.cols.clone(), rows.to_vec())
+        } else {
+            (rows.to_vec(), self.cols.clone())
+        };
+        Transform {
+            shape: self.shape,
+            transpose,
+            rows,
+            cols,
+            digits: labels[..=size].to_vec(),
+        }
+    }
+}
+
+// A small seeded generator, so a seed reproduces the same transform on any
+// platform.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn shuffle<T>(&mut self, mut items: Vec<T>) -> Vec<T> {
+        for i in (1..items.len()).rev() {
+            let j = (self.next() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+        items
+    }
+}
+
+// Every column order that keeps columns within stacks.
+fn arrangements(size: usize, width: usize) -> Vec<Vec<usize>> {
+    let stacks = permutations(size / width);
+    let within = permutations(width);
+    let mut result: Vec<Vec<usize>> = Vec::new();
+    for order in stacks {
+        let mut partial = vec![Vec::new()];
+        for &stack in &order {
+            partial = partial
+                .into_iter()
+                .flat_map(|prefix: Vec<usize>| {
+                    within.iter().map(move |inner| {
+                        let mut cols = prefix.clone();
+                        cols.extend(inner.iter().map(|&i| stack * width + i));
+                        cols
+                    })
+                })
+                .collect();
+        }
+        result.extend(partial);
+    }
+    result
+}
+
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![Vec::new()];
+    }
+    permutations(n - 1)
+        .into_iter()
+        .flat_map(|rest| {
+            (0..n).map(move |i| {
+                let mut order = rest.clone();
+                order.insert(i, n - 1);
+                order
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        constraint::Constraint,
+        solvers::{backtracking::BacktrackingSolver, solver::Solver},
+        validator::Validator,
+    };
+
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    #[test]
+    fn test_rotations_and_reflections() {
+        let sudoku = Sudoku::from_string(PUZZLE).unwrap();
+        let turn = Transform::identity(sudoku.shape()).rotate();
+
+        let turned = turn.apply(&sudoku).unwrap();
+        assert_eq!(turned[0][8], 5, "r1c1 moves to r1c9");
+        assert_eq!(turned[1][8], 3, "r1c2 moves to r2c9");
+        let full_turn = turn.clone().rotate().rotate().rotate();
+        assert_eq!(full_turn, Transform::identity(sudoku.shape()));
+
+        let mirrored = Transform::identity(sudoku.shape()).mirror();
+        assert_eq!(mirrored.apply(&sudoku).unwrap()[0][8], 5);
+        assert!(Validator::is_valid_board(&turned));
+    }
+
+    #[test]
+    fn test_swaps_stay_within_bands() {
+        let identity = Transform::identity(Shape::CLASSIC);
+
+        assert!(identity.clone().swap_rows(0, 2).is_ok());
+        assert!(
+            identity.clone().swap_rows(2, 3).is_err(),
+            "Rows 3 and 4 are in different bands"
+        );
+        assert!(identity.clone().swap_stacks(0, 2).is_ok());
+        assert!(identity.clone().swap_bands(1, 3).is_err());
+        assert!(identity.relabel(&[1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn test_inverse_undoes_transform() {
+        let sudoku = Sudoku::from_string("1.3.5..5.1.32..5.4.6..3.3.2.4..4.3.2").unwrap();
+        let transform = Transform::identity(sudoku.shape())
+            .transpose()
+            .swap_rows(0, 2)
+            .unwrap()
+            .swap_bands(0, 1)
+            .unwrap()
+            .relabel(&[6, 5, 4, 3, 2, 1])
+            .unwrap();
+
+        let moved = transform.apply(&sudoku).unwrap();
+        assert_eq!(moved.shape(), Shape::new(3, 2).unwrap());
+        assert_eq!(transform.inverse().apply(&moved).unwrap(), sudoku);
+    }
+
+    #[test]
+    fn test_equivalent_boards_share_canonical_form() {
+        let sudoku = Sudoku::from_string(PUZZLE).unwrap();
+        let transform = Transform::identity(sudoku.shape())
+            .rotate()
+            .swap_bands(0, 2)
+            .unwrap()
+            .swap_columns(3, 5)
+            .unwrap()
+            .relabel(&[9, 1, 8, 2, 7, 3, 6, 4, 5])
+            .unwrap();
+        let other = transform.apply(&sudoku).unwrap();
+
+        let (canonical, found) = canonical_form(&sudoku).unwrap();
+        assert_eq!(canonical_form(&other).unwrap().0, canonical);
+        assert_eq!(found.apply(&sudoku).unwrap(), canonical);
+        assert!(equivalent(&sudoku, &other).unwrap());
+
+        let mut changed = PUZZLE.to_string();
+        changed.replace_range(2..3, "1");
+        let changed = Sudoku::from_string(&changed).unwrap();
+        assert!(!equivalent(&sudoku, &changed).unwrap());
+    }
+
+    #[test]
+    fn test_variant_boards_are_rejected() {
+        let sudoku = Sudoku::from_string(PUZZLE)
+            .unwrap()
+            .with_constraints(vec![Constraint::Diagonals])
+            .unwrap();
+
+        assert!(Transform::identity(sudoku.shape()).apply(&sudoku).is_err());
+    }
+
+    #[test]
+    fn test_randomize_is_seeded_and_maps_the_solution() {
+        let sudoku = Sudoku::from_string(PUZZLE).unwrap();
+        let mut solution = sudoku.clone();
+        BacktrackingSolver::solve(&mut solution).unwrap();
+
+        let (puzzle, transform) = randomize(&sudoku, 42).unwrap();
+        assert_eq!(randomize(&sudoku, 42).unwrap().0, puzzle, "Same seed");
+        assert_ne!(randomize(&sudoku, 43).unwrap().0, puzzle);
+        assert!(equivalent(&sudoku, &puzzle).unwrap());
+
+        let mapped = transform.apply(&solution).unwrap();
+        let mut solved = puzzle.clone();
+        BacktrackingSolver::solve(&mut solved).unwrap();
+        assert_eq!(mapped, solved, "The transform carries the solution over");
+        assert_eq!(transform.inverse().apply(&puzzle).unwrap(), sudoku);
+
+        let small = Sudoku::from_string("1.3.5..5.1.32..5.4.6..3.3.2.4..4.3.2").unwrap();
+        for seed in 0..20 {
+            let (moved, _) = randomize(&small, seed).unwrap();
+            assert_eq!(moved.shape(), small.shape(), "Seed {} kept the shape", seed);
+            assert!(Validator::is_valid_board(&moved));
+        }
+    }
+
+    #[test]
+    fn test_canonical_form_before_deadline() {
+        let empty = Sudoku::from_string(&"0".repeat(81)).unwrap();
+        let started = Instant::now();
+        let error = canonical_form_before(&empty, started + Duration::from_millis(50)).unwrap_err();
+        assert_eq!(error.to_string(), "Ran out of time");
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        let sudoku = Sudoku::from_string(PUZZLE).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(60);
+        assert_eq!(
+            canonical_form_before(&sudoku, deadline).unwrap(),
+            canonical_form(&sudoku).unwrap()
+        );
+    }
+}
@@ -0,0 +1,398 @@
+use std::{
+    io::{self, Write},
+    time::Duration,
+};
+
+use anyhow::Result;
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    execute, queue,
+    style::{Print, PrintStyledContent, Stylize},
+    terminal::{self, ClearType},
+};
+
+use crate::{
+    cell::{Cell, Digit},
+    game::{GameSession, Move},
+    shape::Shape,
+    solvers::logical::hint,
+    sudoku::Sudoku,
+    validator::Validator,
+};
+
+const HELP: &str =
+    "Arrows move  1-9 enter  Space pencil  Del clear  ? hint  u undo  r redo  q quit";
+
+// Plays `sudoku` in the terminal until the player quits.
+pub fn play(sudoku: Sudoku) -> Result<()> {
+    let mut game = Game::new(sudoku)?;
+    let mut out = io::stdout();
+
+    terminal::enable_raw_mode()?;
+    execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+    let result = run(&mut game, &mut out);
+    execute!(out, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+fn run(game: &mut Game, out: &mut impl Write) -> Result<()> {
+    loop {
+        game.render(out)?;
+        // Wakes up twice a second even without input to keep the timer going.
+        if !event::poll(Duration::from_millis(500))? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+            && let Some(action) = Action::from_key(key)
+        {
+            if action == Action::Quit {
+                return Ok(());
+            }
+            game.apply(action);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Move(isize, isize),
+    // A digit, or 0 to clear the cell.
+    Enter(u8),
+    TogglePencil,
+    Hint,
+    Undo,
+    Redo,
+    Quit,
+}
+
+impl Action {
+    // Letters from `a` to `p` are digits on the larger boards, so commands
+    // use keys outside that range.
+    fn from_key(key: KeyEvent) -> Option<Self> {
+        match key.code {
+            KeyCode::Up => Some(Self::Move(-1, 0)),
+            KeyCode::Down => Some(Self::Move(1, 0)),
+            KeyCode::Left => Some(Self::Move(0, -1)),
+            KeyCode::Right => Some(Self::Move(0, 1)),
+            KeyCode::Backspace | KeyCode::Delete => Some(Self::Enter(0)),
+            KeyCode::Esc | KeyCode::Char('q') => Some(Self::Quit),
+            KeyCode::Char(' ') => Some(Self::TogglePencil),
+            KeyCode::Char('?') => Some(Self::Hint),
+            KeyCode::Char('u') => Some(Self::Undo),
+            KeyCode::Char('r') => Some(Self::Redo),
+            KeyCode::Char(c) => Shape::parse_symbol(c).map(Self::Enter),
+            _ => None,
+        }
+    }
+}
+
+struct Game {
+    session: GameSession,
+    cursor: (usize, usize),
+    pencil: bool,
+    message: String,
+}
+
+impl Game {
+    fn new(board: Sudoku) -> Result<Self> {
+        Ok(Self {
+            session: GameSession::new(board)?,
+            cursor: (0, 0),
+            pencil: false,
+            message: String::new(),
+        })
+    }
+
+    fn apply(&mut self, action: Action) {
+        self.message.clear();
+        match action {
+            Action::Move(rows, cols) => {
+                let size = self.session.board().size() as isize;
+                let (row, col) = self.cursor;
+                self.cursor = (
+                    (row as isize + rows).rem_euclid(size) as usize,
+                    (col as isize + cols).rem_euclid(size) as usize,
+                );
+            }
+            Action::Enter(value) => {
+                if let Err(e) = self.enter(value) {
+                    self.message = e.to_string();
+                }
+            }
+            Action::TogglePencil => self.pencil = !self.pencil,
+            Action::Hint => self.hint(),
+            Action::Undo => {
+                if self.session.undo().is_none() {
+                    self.message = "Nothing to undo".to_string();
+                }
+            }
+            Action::Redo => {
+                if self.session.redo().is_none() {
+                    self.message = "Nothing to redo".to_string();
+                }
+            }
+            Action::Quit => {}
+        }
+    }
+
+    fn enter(&mut self, value: u8) -> Result<()> {
+        let cell = Cell::new(self.cursor.0, self.cursor.1)?;
+        let played = match Digit::new(value) {
+            Err(_) => Move::Erase { cell },
+            Ok(digit) if self.pencil => Move::ToggleCandidate { cell, digit },
+            Ok(digit) => Move::Place { cell, digit },
+        };
+        self.session.play(played)?;
+        if self.session.is_complete() {
+            self.message = format!("Solved in {}!", clock(self.session.elapsed()));
+        }
+        Ok(())
+    }
+
+    // Points at the cell a hint places, and explains it. Hints built on a
+    // wrong entry would be wrong too, so those have to go first.
+    fn hint(&mut self) {
+        let size = self.session.board().size();
+        let has_mistakes = (0..size * size)
+            .filter_map(|index| Cell::new(index / size, index % size).ok())
+            .any(|cell| self.session.is_mistake(cell));
+        if has_mistakes {
+            self.message = "Fix the mistakes first".to_string();
+            return;
+        }
+        self.message = match hint(self.session.board()) {
+            Some(hint) => {
+                if let Some(placement) = hint.placement {
+                    self.cursor = (placement.row, placement.col);
+                }
+                hint.to_string()
+            }
+            None => "No hint available".to_string(),
+        };
+    }
+
+    // The grid laid out like `Display for Sudoku`, with givens in bold,
+    // conflicts in red and the cursor highlighted, then the status lines.
+    fn render(&self, out: &mut impl Write) -> Result<()> {
+        let board = self.session.board();
+        let size = board.size();
+        let (box_rows, box_cols) = match board.regions() {
+            Some(_) => (size, size),
+            None => (board.shape().box_rows(), board.shape().box_cols()),
+        };
+        let boxes_across = size / box_cols;
+        let border = format!("+{}+", "-".repeat(size * 2 + boxes_across * 2 - 1));
+        let separator = format!(
+            "|{}|",
+            vec!["-".repeat(box_cols * 2 + 1); boxes_across].join("+")
+        );
+        let conflicts = Validator::conflicts(board);
+
+        queue!(
+            out,
+            terminal::Clear(ClearType::All),
+            cursor::MoveTo(0, 0),
+            Print(&border),
+            Print("\r\n")
+        )?;
+        for row in 0..size {
+            if row % box_rows == 0 && row != 0 {
+                queue!(out, Print(&separator), Print("\r\n"))?;
+            }
+            queue!(out, Print("| "))?;
+            for col in 0..size {
+                if col % box_cols == 0 && col != 0 {
+                    queue!(out, Print("| "))?;
+                }
+                let value = board[row][col];
+                let mut symbol = match value {
+                    0 => '.'.stylize(),
+                    _ => Shape::symbol(value).stylize(),
+                };
+                if board.is_given(Cell::new(row, col)?) {
+                    symbol = symbol.bold();
+                } else if value != 0 {
+                    symbol = symbol.cyan();
+                }
+                if conflicts.contains(&(row, col)) {
+                    symbol = symbol.red();
+                }
+                if (row, col) == self.cursor {
+                    symbol = symbol.reverse();
+                }
+                queue!(out, PrintStyledContent(symbol), Print(" "))?;
+            }
+            queue!(out, Print("|\r\n"))?;
+        }
+
+        let cell = Cell::new(self.cursor.0, self.cursor.1)?;
+        let marks: Vec<String> = self
+            .session
+            .candidates(cell)
+            .map(|digit| digit.to_string())
+            .collect();
+        let mode = if self.pencil { "pencil" } else { "digits" };
+        queue!(
+            out,
+            Print(&border),
+            Print(format!(
+                "\r\n{}  {}  mistakes: {}  {}  marks: {}\r\n{}\r\n{}\r\n",
+                clock(self.session.elapsed()),
+                cell,
+                self.session.mistakes(),
+                mode,
+                marks.join(" "),
+                self.message,
+                HELP
+            ))
+        )?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+fn clock(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyModifiers;
+
+    use super::*;
+
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    fn game() -> Game {
+        Game::new(Sudoku::from_string(PUZZLE).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_keys_map_to_actions() {
+        let key = |code| Action::from_key(KeyEvent::new(code, KeyModifiers::NONE));
+
+        assert_eq!(key(KeyCode::Up), Some(Action::Move(-1, 0)));
+        assert_eq!(key(KeyCode::Char('7')), Some(Action::Enter(7)));
+        assert_eq!(key(KeyCode::Char('b')), Some(Action::Enter(11)), "Digit 11");
+        assert_eq!(key(KeyCode::Delete), Some(Action::Enter(0)));
+        assert_eq!(key(KeyCode::Char('u')), Some(Action::Undo));
+        assert_eq!(key(KeyCode::Char('x')), None);
+    }
+
+    #[test]
+    fn test_cursor_wraps_around() {
+        let mut game = game();
+
+        game.apply(Action::Move(-1, 0));
+        game.apply(Action::Move(0, -1));
+        assert_eq!(game.cursor, (8, 8));
+        game.apply(Action::Move(1, 1));
+        assert_eq!(game.cursor, (0, 0));
+    }
+
+    #[test]
+    fn test_enter_undo_and_redo() {
+        let mut game = game();
+
+        game.apply(Action::Enter(1));
+        assert_eq!(game.message, "r1c1 is a given");
+        game.apply(Action::Move(0, 2));
+        game.apply(Action::Enter(4));
+        assert_eq!(game.session.board()[0][2], 4);
+
+        game.apply(Action::Undo);
+        assert_eq!(game.session.board()[0][2], 0);
+        game.apply(Action::Redo);
+        assert_eq!(game.session.board()[0][2], 4);
+        game.apply(Action::Enter(0));
+        assert_eq!(game.session.board()[0][2], 0);
+        game.apply(Action::Redo);
+        assert_eq!(
+            game.message, "Nothing to redo",
+            "A new move drops the redo history"
+        );
+    }
+
+    #[test]
+    fn test_pencil_marks_toggle() {
+        let mut game = game();
+        game.apply(Action::Move(0, 2));
+        game.apply(Action::TogglePencil);
+
+        game.apply(Action::Enter(1));
+        game.apply(Action::Enter(4));
+        game.apply(Action::Enter(1));
+        let marks = |game: &Game| game.session.candidates(Cell::new(0, 2).unwrap()).count();
+        assert_eq!(marks(&game), 1);
+        assert_eq!(game.session.board()[0][2], 0, "Marks leave the digit empty");
+
+        game.apply(Action::Enter(0));
+        assert_eq!(marks(&game), 0, "Clearing a cell clears its marks");
+        game.apply(Action::Undo);
+        assert_eq!(marks(&game), 1);
+    }
+
+    #[test]
+    fn test_conflicts_are_found() {
+        let mut game = game();
+        game.apply(Action::Move(0, 2));
+        game.apply(Action::Enter(5));
+
+        assert_eq!(
+            Validator::conflicts(game.session.board()),
+            vec![(0, 0), (0, 2)],
+            "The given 5 in r1c1 clashes"
+        );
+
+        game.apply(Action::Hint);
+        assert_eq!(game.message, "Fix the mistakes first");
+    }
+
+    #[test]
+    fn test_no_hint_on_top_of_a_wrong_digit() {
+        let mut game = game();
+        game.apply(Action::Move(0, 2));
+        game.apply(Action::Enter(2));
+        assert!(Validator::conflicts(game.session.board()).is_empty());
+
+        game.apply(Action::Hint);
+        assert_eq!(game.message, "Fix the mistakes first", "r1c3 is a 4");
+        assert_eq!(game.cursor, (0, 2));
+    }
+
+    #[test]
+    fn test_hint_moves_cursor() {
+        let mut game = game();
+        let expected = hint(game.session.board()).unwrap();
+
+        game.apply(Action::Hint);
+        let placement = expected.placement.unwrap();
+        assert_eq!(game.cursor, (placement.row, placement.col));
+        assert_eq!(game.message, expected.to_string());
+    }
+
+    #[test]
+    fn test_last_digit_stops_the_clock() {
+        let mut almost = SOLUTION.to_string();
+        almost.replace_range(80..81, "0");
+        let mut game = Game::new(Sudoku::from_string(&almost).unwrap()).unwrap();
+
+        game.apply(Action::Move(-1, -1));
+        game.apply(Action::Enter(9));
+        assert!(game.session.is_paused());
+        assert!(game.message.starts_with("Solved in"));
+        game.apply(Action::Enter(0));
+        assert_eq!(game.message, "The puzzle is already solved");
+
+        let mut out = Vec::new();
+        game.render(&mut out).unwrap();
+        assert!(String::from_utf8(out).unwrap().contains(HELP));
+    }
+}
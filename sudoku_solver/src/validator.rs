@@ -1,65 +1,168 @@
+use crate::{constraint::Constraint, regions::Regions, shape::Shape};
+
+// Read access to a square grid of digits, with 0 for an empty cell.
+pub trait Board {
+    fn shape(&self) -> Option<Shape>;
+    fn value(&self, row: usize, col: usize) -> u8;
+
+    // Jigsaw regions used instead of the rectangular boxes.
+    fn regions(&self) -> Option<&Regions> {
+        None
+    }
+
+    // Rules beyond rows, columns and boxes.
+    fn constraints(&self) -> &[Constraint] {
+        &[]
+    }
+}
+
+impl<R: AsRef<[u8]>> Board for [R] {
+    fn shape(&self) -> Option<Shape> {
+        if self.iter().any(|row| row.as_ref().len() != self.len()) {
+            return None;
+        }
+        Shape::for_size(self.len()).ok()
+    }
+
+    fn value(&self, row: usize, col: usize) -> u8 {
+        self[row].as_ref()[col]
+    }
+}
+
+impl<R: AsRef<[u8]>> Board for Vec<R> {
+    fn shape(&self) -> Option<Shape> {
+        self.as_slice().shape()
+    }
+
+    fn value(&self, row: usize, col: usize) -> u8 {
+        self.as_slice().value(row, col)
+    }
+}
+
+impl<R: AsRef<[u8]>, const N: usize> Board for [R; N] {
+    fn shape(&self) -> Option<Shape> {
+        self.as_slice().shape()
+    }
+
+    fn value(&self, row: usize, col: usize) -> u8 {
+        self.as_slice().value(row, col)
+    }
+}
+
 pub struct Validator;
 
 impl Validator {
-    pub fn is_valid(board: &Vec<Vec<u8>>, row: usize, col: usize, value: u8) -> bool {
-        for i in 0..9 {
-            if board[row][i] == value || board[i][col] == value {
-                return false;
-            }
-        }
+    pub fn is_valid<B: Board + ?Sized>(board: &B, row: usize, col: usize, value: u8) -> bool {
+        let Some(shape) = board.shape() else {
+            return false;
+        };
+        let box_row_start = row / shape.box_rows() * shape.box_rows();
+        let box_col_start = col / shape.box_cols() * shape.box_cols();
+        let region = board
+            .regions()
+            .map(|regions| regions.cells(regions.region_of(row, col)));
 
-        let box_row_start = (row / 3) * 3;
-        let box_col_start = (col / 3) * 3;
+        (0..shape.size()).all(|i| {
+            let (box_row, box_col) = match region {
+                Some(cells) => cells[i],
+                None => (
+                    box_row_start + i / shape.box_cols(),
+                    box_col_start + i % shape.box_cols(),
+                ),
+            };
+            board.value(row, i) != value
+                && board.value(i, col) != value
+                && board.value(box_row, box_col) != value
+        }) && board
+            .constraints()
+            .iter()
+            .all(|constraint| constraint.allows(board, row, col, value))
+    }
 
-        for i in 0..3 {
-            for j in 0..3 {
-                if board[box_row_start + i][box_col_start + j] == value {
-                    return false;
-                }
-            }
-        }
+    pub fn is_valid_board<B: Board + ?Sized>(board: &B) -> bool {
+        let Some(shape) = board.shape() else {
+            return false;
+        };
+        let size = shape.size();
 
-        true
+        (0..size).all(|unit| {
+            let row = (0..size).map(|i| (unit, i));
+            let col = (0..size).map(|i| (i, unit));
+            let cells = (0..size).map(|i| match board.regions() {
+                Some(regions) => regions.cells(unit)[i],
+                None => shape.box_cell(unit, i),
+            });
+            Self::has_distinct_digits(board, size, row)
+                && Self::has_distinct_digits(board, size, col)
+                && Self::has_distinct_digits(board, size, cells)
+        }) && board
+            .constraints()
+            .iter()
+            .all(|constraint| constraint.is_satisfied(board))
     }
 
-    pub fn is_valid_board(board: &Vec<Vec<u8>>) -> bool {
-        for i in 0..9 {
-            let mut seen_row = [false; 9];
-            let mut seen_col = [false; 9];
-            for j in 0..9 {
-                if let Some(num_row) = board[i][j].checked_sub(1) {
-                    if seen_row[num_row as usize] {
-                        return false;
-                    }
-                    seen_row[num_row as usize] = true;
-                }
-
-                if let Some(num_col) = board[j][i].checked_sub(1) {
-                    if seen_col[num_col as usize] {
-                        return false;
-                    }
-                    seen_col[num_col as usize] = true;
-                }
+    // Filled cells whose digit clashes with another cell or a constraint, row
+    // by row.
+    pub fn conflicts<B: Board + ?Sized>(board: &B) -> Vec<(usize, usize)> {
+        let Some(shape) = board.shape() else {
+            return Vec::new();
+        };
+        let size = shape.size();
+
+        (0..size * size)
+            .map(|index| (index / size, index % size))
+            .filter(|&(row, col)| {
+                let value = board.value(row, col);
+                let others = Without { board, row, col };
+                value != 0 && !Self::is_valid(&others, row, col, value)
+            })
+            .collect()
+    }
+
+    fn has_distinct_digits<B: Board + ?Sized>(
+        board: &B,
+        size: usize,
+        cells: impl Iterator<Item = (usize, usize)>,
+    ) -> bool {
+        let mut seen = 0u32;
+        for (row, col) in cells {
+            let value = board.value(row, col) as usize;
+            if value > size || (value != 0 && seen & (1 << value) != 0) {
+                return false;
             }
+            seen |= 1 << value;
         }
+        true
+    }
+}
 
-        for box_row in (0..9).step_by(3) {
-            for box_col in (0..9).step_by(3) {
-                let mut seen = [false; 9];
-                for i in 0..3 {
-                    for j in 0..3 {
-                        if let Some(num) = board[box_row + i][box_col + j].checked_sub(1) {
-                            if seen[num as usize] {
-                                return false;
-                            }
-                            seen[num as usize] = true;
-                        }
-                    }
-                }
-            }
+// The board with one cell emptied, to ask whether its digit clashes with the
+// rest.
+struct Without<'a, B: ?Sized> {
+    board: &'a B,
+    row: usize,
+    col: usize,
+}
+
+impl<B: Board + ?Sized> Board for Without<'_, B> {
+    fn shape(&self) -> Option<Shape> {
+        self.board.shape()
+    }
+
+    fn value(&self, row: usize, col: usize) -> u8 {
+        if (row, col) == (self.row, self.col) {
+            0
+        } else {
+            self.board.value(row, col)
         }
+    }
 
-        true
+    fn regions(&self) -> Option<&Regions> {
+        self.board.regions()
+    }
+
+    fn constraints(&self) -> &[Constraint] {
+        self.board.constraints()
     }
 }
 
@@ -102,4 +205,20 @@ mod tests {
         ];
         assert!(!Validator::is_valid_board(&invalid_board));
     }
+
+    #[test]
+    fn test_conflicts() {
+        let mut board = vec![vec![0u8; 4]; 4];
+        board[0][0] = 1;
+        board[1][1] = 1;
+        board[3][2] = 2;
+
+        assert_eq!(
+            Validator::conflicts(&board),
+            vec![(0, 0), (1, 1)],
+            "Same box"
+        );
+        board[1][1] = 0;
+        assert!(Validator::conflicts(&board).is_empty());
+    }
 }
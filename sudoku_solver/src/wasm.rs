@@ -0,0 +1,155 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    cell::{Cell, Digit},
+    solvers::solver::{SolverKind, solve_with_strategy},
+    sudoku::Sudoku,
+    validator::Validator,
+};
+
+// A board as JavaScript sees it, under the name `Sudoku`.
+#[wasm_bindgen(js_name = Sudoku)]
+pub struct WasmSudoku {
+    sudoku: Sudoku,
+}
+
+#[wasm_bindgen(js_class = Sudoku)]
+impl WasmSudoku {
+    // Row by row, with `0` or `.` for empty cells.
+    #[wasm_bindgen(constructor)]
+    pub fn new(text: &str) -> Result<WasmSudoku, JsError> {
+        let sudoku = Sudoku::from_string(text).map_err(js_error)?;
+        Ok(Self { sudoku })
+    }
+
+    // A board in the JSON shape the input files use, variants included.
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<WasmSudoku, JsError> {
+        let sudoku = serde_json::from_str(json).map_err(|err| JsError::new(&err.to_string()))?;
+        Ok(Self { sudoku })
+    }
+
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> Result<String, JsError> {
+        serde_json::to_string(&self.sudoku).map_err(|err| JsError::new(&err.to_string()))
+    }
+
+    #[wasm_bindgen(js_name = toString)]
+    pub fn text(&self) -> String {
+        self.sudoku.to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.sudoku.size()
+    }
+
+    // The digit at a cell, 0 when empty or off the board.
+    pub fn get(&self, row: usize, col: usize) -> u8 {
+        Cell::new(row, col)
+            .ok()
+            .and_then(|cell| self.sudoku.get(cell))
+            .map_or(0, Digit::get)
+    }
+
+    #[wasm_bindgen(js_name = isValid)]
+    pub fn is_valid(&self) -> bool {
+        Validator::is_valid_board(&self.sudoku)
+    }
+
+    // Cells whose digit clashes with another, as `row * size + col`.
+    pub fn conflicts(&self) -> Vec<u32> {
+        Validator::conflicts(&self.sudoku)
+            .into_iter()
+            .map(|(row, col)| (row * self.sudoku.size() + col) as u32)
+            .collect()
+    }
+
+    // Fills in the board, which is left as it was if there is no solution.
+    pub fn solve(&mut self, kind: SolverKind) -> Result<(), JsError> {
+        if !self.is_valid() {
+            return Err(JsError::new("Sudoku is invalid"));
+        }
+        let mut solution = self.sudoku.clone();
+        solve_with_strategy(&mut solution, kind).map_err(js_error)?;
+        self.sudoku = solution;
+        Ok(())
+    }
+}
+
+fn js_error(err: anyhow::Error) -> JsError {
+    JsError::new(&err.to_string())
+}
+
+// The same tests run natively and, with `cargo test-wasm`, under Node.js.
+// Errors can only be built inside a JavaScript runtime.
+#[cfg(test)]
+mod tests {
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    use super::*;
+
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    #[test]
+    fn test_parse_and_read_cells() {
+        let sudoku = WasmSudoku::new(PUZZLE).unwrap();
+
+        assert_eq!(sudoku.size(), 9);
+        assert_eq!(sudoku.get(0, 0), 5);
+        assert_eq!(sudoku.get(0, 2), 0);
+        assert_eq!(sudoku.get(30, 0), 0, "Off the board");
+        assert_eq!(sudoku.text(), PUZZLE);
+
+        let json = sudoku.to_json().unwrap();
+        assert_eq!(WasmSudoku::from_json(&json).unwrap().text(), PUZZLE);
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let json = r#"{"board": [[1,1,0,0],[0,0,0,0],[0,0,0,0],[0,0,0,0]]}"#;
+        let sudoku = WasmSudoku::from_json(json).unwrap();
+
+        assert!(!sudoku.is_valid());
+        assert_eq!(sudoku.conflicts(), vec![0, 1], "Two 1s in the first row");
+        assert!(WasmSudoku::new(PUZZLE).unwrap().conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_solve_with_every_strategy() {
+        for kind in [
+            SolverKind::Backtracking,
+            SolverKind::Heuristic,
+            SolverKind::Logical,
+        ] {
+            let mut sudoku = WasmSudoku::new(PUZZLE).unwrap();
+            sudoku.solve(kind).unwrap();
+            assert_eq!(sudoku.text(), SOLUTION);
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    #[test]
+    fn test_errors_reach_javascript() {
+        assert!(WasmSudoku::new("123").is_err());
+        assert!(WasmSudoku::from_json("{").is_err());
+
+        let mut stuck = WasmSudoku::new("1200004000030000").unwrap();
+        assert!(stuck.solve(SolverKind::Backtracking).is_err());
+        assert_eq!(stuck.text(), "1200004000030000", "Left as it was");
+
+        let arrow = r#"{
+            "board": [[0,3,0,0],[0,0,0,2],[0,0,0,0],[0,0,4,0]],
+            "constraints": [{"type": "arrow", "circle": [2,0], "cells": [[2,1],[2,2]]}]
+        }"#;
+        let mut arrow = WasmSudoku::from_json(arrow).unwrap();
+        assert!(
+            arrow.solve(SolverKind::Logical).is_err(),
+            "The arrow can't add up"
+        );
+    }
+}